@@ -0,0 +1,48 @@
+//! A tiny timing harness so benchmarks don't need any dependencies. Run with `cargo bench`.
+
+// Not every benchmark uses every helper.
+#![allow(dead_code)]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Runs `f` until roughly a second has passed and prints the average time per call.
+pub fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    // Warm up caches and branch predictors.
+    for _ in 0..3 {
+        black_box(f());
+    }
+
+    let budget = Duration::from_secs(1);
+    let start = Instant::now();
+    let mut iterations = 0;
+    while start.elapsed() < budget {
+        black_box(f());
+        iterations += 1;
+    }
+
+    let per_iteration = start.elapsed() / iterations;
+    println!("{name:<48} {per_iteration:>12?}/iter ({iterations} iterations)");
+    per_iteration
+}
+
+/// A small deterministic generator for building large inputs.
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A value in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
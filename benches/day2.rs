@@ -0,0 +1,38 @@
+use aoc_2022::day2;
+
+mod common;
+
+use common::{bench, XorShift};
+
+fn generate_guide(rounds: usize, line_ending: &str) -> String {
+    let mut rng = XorShift::new(2);
+    let mut guide = String::with_capacity(rounds * (3 + line_ending.len()));
+    for _ in 0..rounds {
+        guide.push((b'A' + rng.below(3) as u8) as char);
+        guide.push(' ');
+        guide.push((b'X' + rng.below(3) as u8) as char);
+        guide.push_str(line_ending);
+    }
+    guide
+}
+
+fn main() {
+    let lf = generate_guide(100_000, "\n");
+    let crlf = generate_guide(100_000, "\r\n");
+    let mut no_trailing_newline = lf.clone();
+    no_trailing_newline.pop();
+
+    bench("day2 part1 (LF, fast path)", || day2::part1(&lf));
+    bench("day2 part1 (CRLF)", || day2::part1(&crlf));
+    bench("day2 part1 (no trailing newline)", || {
+        day2::part1(&no_trailing_newline)
+    });
+    bench("day2 part2 (LF, fast path)", || day2::part2(&lf));
+    bench("day2 part2 (CRLF)", || day2::part2(&crlf));
+    bench("day2 parse_guide (LF, fast path)", || {
+        day2::guide::parse_guide(&lf)
+    });
+    bench("day2 parse_guide (CRLF)", || {
+        day2::guide::parse_guide(&crlf)
+    });
+}
@@ -0,0 +1,61 @@
+use aoc_2022::day5::crane::{CrateMover9000, CrateMover9001};
+use aoc_2022::day5::{self, segments, Instruction};
+
+mod common;
+
+use common::{bench, XorShift};
+
+/// A procedure for `num_stacks` stacks of `height` crates where each move takes a random number
+/// of crates off a random stack, so moves get very big.
+fn generate_input(num_stacks: usize, height: usize, moves: usize) -> String {
+    let mut rng = XorShift::new(5);
+    let stacks: Vec<Vec<String>> = (0..num_stacks)
+        .map(|_| {
+            (0..height)
+                .map(|_| ((b'A' + rng.below(26) as u8) as char).to_string())
+                .collect()
+        })
+        .collect();
+
+    let mut lens = vec![height; num_stacks];
+    let instructions: Vec<Instruction> = (0..moves)
+        .map(|_| {
+            let start = rng.below(num_stacks as u64) as usize;
+            let end = rng.below(num_stacks as u64) as usize;
+            let num_crates = rng.below(lens[start] as u64 + 1) as usize;
+            lens[start] -= num_crates;
+            lens[end] += num_crates;
+            Instruction {
+                num_crates,
+                start_stack: start + 1,
+                end_stack: end + 1,
+            }
+        })
+        .collect();
+
+    day5::write_input(&stacks, &instructions)
+}
+
+fn main() {
+    let small = generate_input(9, 50, 500);
+    let big = generate_input(9, 20_000, 5_000);
+
+    bench("day5 9000 small (vec stacks)", || {
+        day5::rearrange(&small, CrateMover9000)
+    });
+    bench("day5 9000 small (segmented)", || {
+        segments::rearrange(&small, CrateMover9000)
+    });
+    bench("day5 9000 big moves (vec stacks)", || {
+        day5::rearrange(&big, CrateMover9000)
+    });
+    bench("day5 9000 big moves (segmented)", || {
+        segments::rearrange(&big, CrateMover9000)
+    });
+    bench("day5 9001 big moves (vec stacks)", || {
+        day5::rearrange(&big, CrateMover9001)
+    });
+    bench("day5 9001 big moves (segmented)", || {
+        segments::rearrange(&big, CrateMover9001)
+    });
+}
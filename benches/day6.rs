@@ -0,0 +1,46 @@
+use aoc_2022::day6;
+
+mod common;
+
+use common::{bench, XorShift};
+
+/// A long stream with no marker of `window_len` (it only uses `window_len - 1` letters) until
+/// the very end.
+fn generate_stream(len: usize, window_len: usize) -> String {
+    let mut rng = XorShift::new(6);
+    let mut stream: String = (0..len)
+        .map(|_| (b'!' + rng.below(window_len as u64 - 1) as u8) as char)
+        .collect();
+    stream.extend((0..window_len).map(|offset| (b'!' + offset as u8) as char));
+    stream
+}
+
+fn main() {
+    let short = generate_stream(1_000_000, 4);
+    let long = generate_stream(1_000_000, 14);
+    let wide = generate_stream(1_000_000, 64);
+
+    bench("day6 window 4 (sorting)", || {
+        day6::find_marker_sorting::<4>(&short)
+    });
+    bench("day6 window 4 (counts)", || day6::find_marker(&short, 4));
+    bench("day6 window 4 (skipping)", || {
+        day6::find_marker_skipping(&short, 4)
+    });
+
+    bench("day6 window 14 (sorting)", || {
+        day6::find_marker_sorting::<14>(&long)
+    });
+    bench("day6 window 14 (counts)", || day6::find_marker(&long, 14));
+    bench("day6 window 14 (skipping)", || {
+        day6::find_marker_skipping(&long, 14)
+    });
+
+    bench("day6 window 64 (sorting)", || {
+        day6::find_marker_sorting::<64>(&wide)
+    });
+    bench("day6 window 64 (counts)", || day6::find_marker(&wide, 64));
+    bench("day6 window 64 (skipping)", || {
+        day6::find_marker_skipping(&wide, 64)
+    });
+}
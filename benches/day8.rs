@@ -0,0 +1,56 @@
+use aoc_2022::day8::{self, forest::Forest};
+
+mod common;
+
+use common::{bench, XorShift};
+
+/// A `size` by `size` forest where each tree is `9` with probability `1 / tall_one_in` and a
+/// random lower height otherwise.
+fn generate_forest(size: usize, tall_one_in: u64) -> String {
+    let mut rng = XorShift::new(8);
+    let mut forest = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        forest.extend((0..size).map(|_| {
+            if rng.below(tall_one_in) == 0 {
+                '9'
+            } else {
+                char::from(b'0' + rng.below(9) as u8)
+            }
+        }));
+        forest.push('\n');
+    }
+    forest
+}
+
+fn main() {
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let random = generate_forest(2000, 10);
+    let sparse = generate_forest(2000, 1000);
+
+    for (name, forest) in [("random", &random), ("sparse tall trees", &sparse)] {
+        bench(&format!("day8 {name} visible (naive)"), || {
+            day8::naive::count_visible(forest)
+        });
+        bench(&format!("day8 {name} visible (forest)"), || {
+            Forest::parse(forest).unwrap().count_visible()
+        });
+        bench(&format!("day8 {name} visible ({threads} threads)"), || {
+            Forest::parse(forest)
+                .unwrap()
+                .threads(threads)
+                .count_visible()
+        });
+        bench(&format!("day8 {name} scenic (naive)"), || {
+            day8::naive::max_scenic_score(forest)
+        });
+        bench(&format!("day8 {name} scenic (forest)"), || {
+            Forest::parse(forest).unwrap().max_scenic_score()
+        });
+        bench(&format!("day8 {name} scenic ({threads} threads)"), || {
+            Forest::parse(forest)
+                .unwrap()
+                .threads(threads)
+                .max_scenic_score()
+        });
+    }
+}
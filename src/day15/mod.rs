@@ -1,6 +1,8 @@
 use std::collections::HashSet;
 use std::ops::RangeInclusive;
 
+use crate::interval_set::IntervalSet;
+
 // Not sure but I think y values can't be negative. I should check this against my input though it
 // doesn't really matter.
 pub fn part1((input, row): (&str, u64)) -> u64 {
@@ -24,38 +26,15 @@ pub fn part1((input, row): (&str, u64)) -> u64 {
         dead_zones_in_row.push(sensor.0 - width..=sensor.0 + width);
     }
 
-    // First, consolidate ranges. Itertools has something for this... Coalesce?
-    dead_zones_in_row.sort_unstable_by_key(|range| *range.start());
-    let mut consolidated_zones = Vec::new();
-
-    let range = &dead_zones_in_row[0];
-    let mut range = (*range.start(), *range.end());
-    for next_range in dead_zones_in_row.into_iter().skip(1) {
-        // Note that ranges that are _touching but not overlapping_ should be combined.
-        if *next_range.start() <= range.1 + 1 {
-            if *next_range.end() > range.1 {
-                range.1 = *next_range.end();
-            }
-        } else {
-            consolidated_zones.push(range);
-            range = (*next_range.start(), *next_range.end());
-        }
-    }
-    consolidated_zones.push(range);
+    // First, consolidate ranges.
+    let consolidated_zones: IntervalSet<i64> = dead_zones_in_row.into_iter().collect();
 
     // Second, Get the total width covered
-    let mut count = consolidated_zones
-        .iter()
-        .map(|(start, end)| (*end - *start + 1) as u64)
-        .sum();
+    let mut count = consolidated_zones.len();
 
     // Finally, subtract known beacons
-    // I think the zones here are still sorted so this could be a binary search but :shrug:
     for beacon in beacons_in_row {
-        let counted = consolidated_zones
-            .iter()
-            .any(|(start, end)| *start <= beacon && beacon <= *end);
-        if counted {
+        if consolidated_zones.contains(beacon) {
             count -= 1;
         }
     }
@@ -107,36 +86,18 @@ pub fn part2((input, x_range, y_range): (&str, RangeInclusive<i64>, RangeInclusi
         }
     }
 
-    // First, consolidate ranges. Itertools has something for this... Coalesce?
-    no_distress_beacon_ranges.sort_unstable_by_key(|range| *range.start());
-    let mut consolidated_zones = Vec::new();
-
-    let range = &no_distress_beacon_ranges[0];
-    let mut range = (*range.start(), *range.end());
-    for next_range in no_distress_beacon_ranges.into_iter().skip(1) {
-        // Note that ranges that are _touching but not overlapping_ should be combined.
-        if *next_range.start() <= range.1 + 1 {
-            if *next_range.end() > range.1 {
-                range.1 = *next_range.end();
-            }
-        } else {
-            consolidated_zones.push(range);
-            range = (*next_range.start(), *next_range.end());
-        }
-    }
-    consolidated_zones.push(range);
-
-    // Two cases here: either there are two ranges with a missing spot between or there is a single
-    // range missing the first or last item in the grid. Find that missing spot.
-    let box_idx = if consolidated_zones.len() == 2 {
-        consolidated_zones[0].1 + 1
-    } else if consolidated_zones[0].0 == 0 {
-        // Our single zone includes idx 0 so it must be missing the last idx
-        consolidated_zones[0].1
-    } else {
-        // Our single zone includes must not have idx 0
-        0
-    };
+    // First, consolidate ranges.
+    let consolidated_zones: IntervalSet<i64> = no_distress_beacon_ranges.into_iter().collect();
+
+    // Then whatever isn't covered (there should only be one spot) is the distress beacon.
+    let first_idx = x_range.start() + y_range.start() * box_width;
+    let last_idx = x_range.end() + y_range.end() * box_width;
+    let box_idx = *consolidated_zones
+        .complement(first_idx..=last_idx)
+        .iter()
+        .next()
+        .expect("There should be a spot for the distress beacon")
+        .start();
 
     // Finally, convert from our box indices to the tuning frequencies.
     let x = box_idx % box_width;
@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::hand::RockPaperScissors;
+
+/// The second column of the strategy guide. What it means depends on which part we're solving.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Code {
+    X,
+    Y,
+    Z,
+}
+
+impl Code {
+    /// In part 1 we thought "X" meant Rock which is "A", etc.
+    pub fn as_hand(self) -> RockPaperScissors {
+        match self {
+            Self::X => RockPaperScissors::Rock,
+            Self::Y => RockPaperScissors::Paper,
+            Self::Z => RockPaperScissors::Scissors,
+        }
+    }
+
+    /// In part 2 we know that "X" means we need to lose (e.g. `Ordering::Less`)
+    pub fn as_result(self) -> Ordering {
+        match self {
+            Self::X => Ordering::Less,
+            Self::Y => Ordering::Equal,
+            Self::Z => Ordering::Greater,
+        }
+    }
+}
+
+impl TryFrom<u8> for Code {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'X' => Ok(Self::X),
+            b'Y' => Ok(Self::Y),
+            b'Z' => Ok(Self::Z),
+            _ => Err(value),
+        }
+    }
+}
+
+/// One line of the strategy guide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuideEntry {
+    pub opponent: RockPaperScissors,
+    pub code: Code,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1 indexed, like an editor would show it.
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    InvalidOpponent(char),
+    InvalidCode(char),
+    /// The line isn't two symbols separated by a single space.
+    Malformed(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseErrorKind::InvalidOpponent(c) => {
+                write!(f, "expected opponent hand A, B or C but found {c:?}")
+            }
+            ParseErrorKind::InvalidCode(c) => write!(f, "expected X, Y or Z but found {c:?}"),
+            ParseErrorKind::Malformed(line) => {
+                write!(f, "expected a line like \"A X\" but found {line:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a whole strategy guide. Lines can end in `\n` or `\r\n`, the final newline is optional
+/// and blank lines are skipped.
+pub fn parse_guide(input: &str) -> Result<Vec<GuideEntry>, ParseError> {
+    entries(input).collect()
+}
+
+/// Lazily parses the strategy guide.
+///
+/// Nearly every guide is a series of `"A X\n"` lines (maybe missing the final newline) so we check
+/// for that up front, which is a single pass over bytes we're about to read anyway, and skip
+/// straight to decoding if so. Anything else goes through the slower line-by-line parser.
+pub fn entries(input: &str) -> impl Iterator<Item = Result<GuideEntry, ParseError>> + '_ {
+    let bytes = input.as_bytes();
+    let (body, last_line) = bytes.split_at(bytes.len() - bytes.len() % 4);
+    let well_formed = body.chunks_exact(4).all(is_well_formed)
+        && matches!(last_line, [] | [b'A'..=b'C', b' ', b'X'..=b'Z']);
+
+    let (fast, slow) = if well_formed {
+        (Some(bytes.chunks(4).map(decode_well_formed)), None)
+    } else {
+        (None, Some(parse_lines(input)))
+    };
+
+    fast.into_iter()
+        .flatten()
+        .map(Ok)
+        .chain(slow.into_iter().flatten())
+}
+
+fn is_well_formed(line: &[u8]) -> bool {
+    matches!(line, [b'A'..=b'C', b' ', b'X'..=b'Z', b'\n'])
+}
+
+fn decode_well_formed(line: &[u8]) -> GuideEntry {
+    // `is_well_formed` already checked these.
+    GuideEntry {
+        opponent: line[0].try_into().expect("Opponent hand was validated"),
+        code: line[2].try_into().expect("Code was validated"),
+    }
+}
+
+fn parse_lines(input: &str) -> impl Iterator<Item = Result<GuideEntry, ParseError>> + '_ {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            parse_line(line).map_err(|kind| ParseError {
+                line: idx + 1,
+                kind,
+            })
+        })
+}
+
+fn parse_line(line: &str) -> Result<GuideEntry, ParseErrorKind> {
+    let (opponent, code) = match line.trim_end().as_bytes() {
+        [opponent, b' ', code] => (*opponent, *code),
+        _ => return Err(ParseErrorKind::Malformed(line.to_string())),
+    };
+
+    let opponent = RockPaperScissors::try_from(opponent)
+        .map_err(|b| ParseErrorKind::InvalidOpponent(b as char))?;
+    let code = Code::try_from(code).map_err(|b| ParseErrorKind::InvalidCode(b as char))?;
+
+    Ok(GuideEntry { opponent, code })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPECTED: [GuideEntry; 3] = [
+        GuideEntry {
+            opponent: RockPaperScissors::Rock,
+            code: Code::Y,
+        },
+        GuideEntry {
+            opponent: RockPaperScissors::Paper,
+            code: Code::X,
+        },
+        GuideEntry {
+            opponent: RockPaperScissors::Scissors,
+            code: Code::Z,
+        },
+    ];
+
+    #[test]
+    fn well_formed() {
+        assert_eq!(parse_guide("A Y\nB X\nC Z\n").unwrap(), EXPECTED);
+    }
+
+    #[test]
+    fn missing_trailing_newline() {
+        assert_eq!(parse_guide("A Y\nB X\nC Z").unwrap(), EXPECTED);
+    }
+
+    #[test]
+    fn crlf() {
+        assert_eq!(parse_guide("A Y\r\nB X\r\nC Z\r\n").unwrap(), EXPECTED);
+    }
+
+    #[test]
+    fn blank_lines() {
+        assert_eq!(parse_guide("\nA Y\n\nB X\nC Z\n\n").unwrap(), EXPECTED);
+    }
+
+    #[test]
+    fn invalid_opponent() {
+        let err = parse_guide("A Y\nD X\nC Z\n").unwrap_err();
+
+        assert_eq!(
+            err,
+            ParseError {
+                line: 2,
+                kind: ParseErrorKind::InvalidOpponent('D'),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 2: expected opponent hand A, B or C but found 'D'"
+        );
+    }
+
+    #[test]
+    fn invalid_code() {
+        let err = parse_guide("A Y\r\nB X\r\nC W\r\n").unwrap_err();
+
+        assert_eq!(
+            err,
+            ParseError {
+                line: 3,
+                kind: ParseErrorKind::InvalidCode('W'),
+            }
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let err = parse_guide("A Y\nBX\n").unwrap_err();
+
+        assert_eq!(
+            err,
+            ParseError {
+                line: 2,
+                kind: ParseErrorKind::Malformed("BX".to_string()),
+            }
+        );
+    }
+}
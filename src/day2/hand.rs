@@ -32,18 +32,10 @@ impl RockPaperScissors {
         //
         // -1 => we're Rock and we want a losing hand so we have to generate Scissors which is 2.
         // 3 => we're Scissors and we want a winning hand so we have to generate Rock which is 0.
-        //
-        // We can do this branchlessly by working mod 3:
-        //
-        //    let mapped_value = new_value.rem_euclid(3) as u8;
-        //
-        // But the resulting assembly is longer so it's not obvious which is faster. Will need to
-        // benchmark and see if it vectorizes.
         match new_value {
-            -1 => Self::Scissors,
-            3 => Self::Rock,
-            // Hopefully the compiler eliminates this addition since `.into()` subtracts it.
-            n => (n as u8 + b'A').into(),
+            -1 | 2 => Self::Scissors,
+            0 | 3 => Self::Rock,
+            _ => Self::Paper,
         }
     }
 }
@@ -64,12 +56,16 @@ impl Ord for RockPaperScissors {
     }
 }
 
-impl From<u8> for RockPaperScissors {
-    fn from(value: u8) -> Self {
-        match value - b'A' {
-            0 => Self::Rock,
-            1 => Self::Paper,
-            _ => Self::Scissors,
+/// Converts the opponent's column of the strategy guide. The error is the byte we didn't expect.
+impl TryFrom<u8> for RockPaperScissors {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'A' => Ok(Self::Rock),
+            b'B' => Ok(Self::Paper),
+            b'C' => Ok(Self::Scissors),
+            _ => Err(value),
         }
     }
 }
@@ -80,28 +76,34 @@ mod tests {
 
     #[test]
     fn example1() {
-        let opponent: RockPaperScissors = b'A'.into();
-        let us: RockPaperScissors = b'B'.into();
+        let opponent: RockPaperScissors = b'A'.try_into().unwrap();
+        let us: RockPaperScissors = b'B'.try_into().unwrap();
 
         assert_eq!(us.play_against(&opponent), 8);
     }
 
     #[test]
     fn example2() {
-        let opponent: RockPaperScissors = b'B'.into();
-        let us: RockPaperScissors = b'A'.into();
+        let opponent: RockPaperScissors = b'B'.try_into().unwrap();
+        let us: RockPaperScissors = b'A'.try_into().unwrap();
 
         assert_eq!(us.play_against(&opponent), 1);
     }
 
     #[test]
     fn example3() {
-        let opponent: RockPaperScissors = b'C'.into();
-        let us: RockPaperScissors = b'C'.into();
+        let opponent: RockPaperScissors = b'C'.try_into().unwrap();
+        let us: RockPaperScissors = b'C'.try_into().unwrap();
 
         assert_eq!(us.play_against(&opponent), 6);
     }
 
+    #[test]
+    fn invalid_byte() {
+        assert_eq!(RockPaperScissors::try_from(b'D'), Err(b'D'));
+        assert_eq!(RockPaperScissors::try_from(b'X'), Err(b'X'));
+    }
+
     #[test]
     fn test_generate_hand() {
         assert_eq!(
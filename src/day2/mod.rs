@@ -1,61 +1,41 @@
-use std::cmp::Ordering;
-
-use hand::RockPaperScissors;
+use guide::ParseError;
+pub use hand::RockPaperScissors;
 
+pub mod guide;
 mod hand;
+pub mod tournament;
 
 pub fn part1(input: &str) -> u64 {
-    input
-        .as_bytes()
-        .chunks_exact(4)
-        .map(|line| {
-            let opponent = line[0];
-            let us = line[2];
+    try_part1(input).unwrap_or_else(|err| panic!("Invalid strategy guide: {err}"))
+}
 
-            let opponent = RockPaperScissors::from(opponent);
-            let us = RockPaperScissors::from(part1_mapping(us));
+pub fn try_part1(input: &str) -> Result<u64, ParseError> {
+    guide::entries(input)
+        .map(|entry| {
+            let entry = entry?;
+            let us = entry.code.as_hand();
 
-            us.play_against(&opponent)
+            Ok(us.play_against(&entry.opponent))
         })
         .sum()
 }
 
-// In part 1 we thought "X" meant Rock which is "A", etc.
-fn part1_mapping(b: u8) -> u8 {
-    match b {
-        b'X' => b'A',
-        b'Y' => b'B',
-        _ => b'C',
-    }
-}
-
 pub fn part2(input: &str) -> u64 {
-    input
-        .as_bytes()
-        .chunks_exact(4)
-        .map(|line| {
-            let opponent = line[0];
-            let us = line[2];
-
-            let opponent = RockPaperScissors::from(opponent);
+    try_part2(input).unwrap_or_else(|err| panic!("Invalid strategy guide: {err}"))
+}
 
-            let needed_result = part2_mapping(us);
-            let us = opponent.generate_hand(needed_result);
+pub fn try_part2(input: &str) -> Result<u64, ParseError> {
+    guide::entries(input)
+        .map(|entry| {
+            let entry = entry?;
+            let needed_result = entry.code.as_result();
+            let us = entry.opponent.generate_hand(needed_result);
 
-            us.play_against(&opponent)
+            Ok(us.play_against(&entry.opponent))
         })
         .sum()
 }
 
-// In part 2 we know that "X" means we need to lose (e.g. `Ordering::Less`)
-fn part2_mapping(b: u8) -> Ordering {
-    match b {
-        b'X' => Ordering::Less,
-        b'Y' => Ordering::Equal,
-        _ => Ordering::Greater,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,6 +50,20 @@ mod tests {
             assert_eq!(part1(input), 15);
         }
 
+        #[test]
+        fn crlf_without_trailing_newline() {
+            let input = "A Y\r\nB X\r\nC Z";
+
+            assert_eq!(part1(input), 15);
+        }
+
+        #[test]
+        fn invalid_symbol() {
+            let input = "A Y\nB Q\nC Z\n";
+
+            assert_eq!(try_part1(input).unwrap_err().line, 2);
+        }
+
         #[test]
         fn my_input() {
             let input = include_str!("./input.txt");
@@ -88,6 +82,13 @@ mod tests {
             assert_eq!(part2(input), 12);
         }
 
+        #[test]
+        fn crlf_without_trailing_newline() {
+            let input = "A Y\r\nB X\r\nC Z";
+
+            assert_eq!(part2(input), 12);
+        }
+
         #[test]
         fn my_input() {
             let input = include_str!("./input.txt");
@@ -0,0 +1,533 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::guide::{self, ParseError};
+use super::hand::RockPaperScissors;
+
+/// A single round as seen by one of the players.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Round {
+    pub ours: RockPaperScissors,
+    pub theirs: RockPaperScissors,
+}
+
+/// Something that can pick a hand to throw given everything that's happened so far in the match.
+pub trait Strategy {
+    fn name(&self) -> &str;
+
+    /// Picks the next hand. `history` is from this strategy's point of view so `round.ours` is
+    /// always what this strategy threw.
+    fn play(&mut self, history: &[Round]) -> RockPaperScissors;
+
+    /// Called before every match so strategies with internal state start fresh.
+    fn reset(&mut self) {}
+}
+
+/// Throws a fixed sequence of hands, looping back to the start when it runs out.
+#[derive(Clone, Debug)]
+pub struct FixedSequence {
+    name: String,
+    hands: Vec<RockPaperScissors>,
+}
+
+/// Why a `FixedSequence` couldn't be read from a strategy guide.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GuideStrategyError {
+    Parse(ParseError),
+    /// The guide has no rounds in it so there's nothing to throw.
+    Empty,
+}
+
+impl FixedSequence {
+    /// Panics if `hands` is empty.
+    pub fn new(name: impl Into<String>, hands: Vec<RockPaperScissors>) -> Self {
+        assert!(!hands.is_empty(), "Fixed sequence needs at least one hand");
+        Self {
+            name: name.into(),
+            hands,
+        }
+    }
+
+    /// Reads our column of a strategy guide with the part 1 interpretation ("X" means Rock etc.).
+    pub fn from_guide(name: impl Into<String>, guide: &str) -> Result<Self, GuideStrategyError> {
+        let hands = guide::entries(guide)
+            .map(|entry| entry.map(|entry| entry.code.as_hand()))
+            .collect::<Result<_, _>>()?;
+        Self::non_empty(name, hands)
+    }
+
+    /// Reads the opponent's column of a strategy guide.
+    pub fn opponent_from_guide(
+        name: impl Into<String>,
+        guide: &str,
+    ) -> Result<Self, GuideStrategyError> {
+        let hands = guide::entries(guide)
+            .map(|entry| entry.map(|entry| entry.opponent))
+            .collect::<Result<_, _>>()?;
+        Self::non_empty(name, hands)
+    }
+
+    fn non_empty(
+        name: impl Into<String>,
+        hands: Vec<RockPaperScissors>,
+    ) -> Result<Self, GuideStrategyError> {
+        if hands.is_empty() {
+            return Err(GuideStrategyError::Empty);
+        }
+        Ok(Self::new(name, hands))
+    }
+}
+
+impl From<ParseError> for GuideStrategyError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl fmt::Display for GuideStrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(err) => err.fmt(f),
+            Self::Empty => write!(f, "the guide has no rounds"),
+        }
+    }
+}
+
+impl std::error::Error for GuideStrategyError {}
+
+impl Strategy for FixedSequence {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn play(&mut self, history: &[Round]) -> RockPaperScissors {
+        self.hands[history.len() % self.hands.len()]
+    }
+}
+
+/// Assumes the opponent will throw whatever they've thrown most so far and beats it.
+#[derive(Clone, Debug, Default)]
+pub struct FrequencyCounter;
+
+impl Strategy for FrequencyCounter {
+    fn name(&self) -> &str {
+        "frequency counter"
+    }
+
+    fn play(&mut self, history: &[Round]) -> RockPaperScissors {
+        let mut counts = [0; 3];
+        for round in history {
+            counts[round.theirs as usize] += 1;
+        }
+        counter(most_common(counts))
+    }
+}
+
+/// Looks for earlier places where the opponent's last `order` hands appeared and assumes they'll
+/// repeat whatever came next. Falls back to frequency counting if the pattern hasn't been seen.
+#[derive(Clone, Debug)]
+pub struct PatternMatcher {
+    order: usize,
+}
+
+impl PatternMatcher {
+    pub fn new(order: usize) -> Self {
+        assert!(order > 0, "Pattern needs at least one hand");
+        Self { order }
+    }
+}
+
+impl Strategy for PatternMatcher {
+    fn name(&self) -> &str {
+        "pattern matcher"
+    }
+
+    fn play(&mut self, history: &[Round]) -> RockPaperScissors {
+        if history.len() <= self.order {
+            return FrequencyCounter.play(history);
+        }
+
+        let pattern = &history[history.len() - self.order..];
+        let mut counts = [0; 3];
+        for (idx, window) in history.windows(self.order).enumerate() {
+            // The last window is the pattern itself and there's nothing after it yet.
+            let Some(next) = history.get(idx + self.order) else {
+                break;
+            };
+            let matches = window
+                .iter()
+                .zip(pattern)
+                .all(|(a, b)| a.theirs == b.theirs);
+            if matches {
+                counts[next.theirs as usize] += 1;
+            }
+        }
+
+        if counts == [0; 3] {
+            FrequencyCounter.play(history)
+        } else {
+            counter(most_common(counts))
+        }
+    }
+}
+
+/// Throws uniformly random hands from a seeded xorshift generator so tournaments are repeatable.
+#[derive(Clone, Debug)]
+pub struct SeededRandom {
+    seed: u64,
+    state: u64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck at zero forever.
+        let seed = if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        };
+        Self { seed, state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+impl Strategy for SeededRandom {
+    fn name(&self) -> &str {
+        "seeded random"
+    }
+
+    fn play(&mut self, _history: &[Round]) -> RockPaperScissors {
+        match self.next_u64() % 3 {
+            0 => RockPaperScissors::Rock,
+            1 => RockPaperScissors::Paper,
+            _ => RockPaperScissors::Scissors,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = self.seed;
+    }
+}
+
+// Ties go to the earlier hand which keeps things deterministic.
+fn most_common(counts: [u32; 3]) -> RockPaperScissors {
+    let idx = (0..3).rev().max_by_key(|&idx| counts[idx]).unwrap_or(0);
+    match idx {
+        0 => RockPaperScissors::Rock,
+        1 => RockPaperScissors::Paper,
+        _ => RockPaperScissors::Scissors,
+    }
+}
+
+fn counter(hand: RockPaperScissors) -> RockPaperScissors {
+    hand.generate_hand(Ordering::Greater)
+}
+
+/// The outcome of two strategies playing each other for a number of rounds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchResult {
+    /// Indices of the two players in the tournament.
+    pub players: [usize; 2],
+    pub scores: [u64; 2],
+    /// Rounds won by each player.
+    pub wins: [u64; 2],
+    pub draws: u64,
+}
+
+impl MatchResult {
+    /// The index of the player with the higher score, if there was one.
+    pub fn winner(&self) -> Option<usize> {
+        match self.scores[0].cmp(&self.scores[1]) {
+            Ordering::Greater => Some(self.players[0]),
+            Ordering::Less => Some(self.players[1]),
+            Ordering::Equal => None,
+        }
+    }
+}
+
+/// Plays `rounds` rounds between two strategies, scoring each round like the puzzle does.
+pub fn play_match(
+    first: &mut dyn Strategy,
+    second: &mut dyn Strategy,
+    rounds: usize,
+) -> MatchResult {
+    first.reset();
+    second.reset();
+
+    let mut first_history = Vec::with_capacity(rounds);
+    let mut second_history = Vec::with_capacity(rounds);
+    let mut result = MatchResult {
+        players: [0, 1],
+        scores: [0; 2],
+        wins: [0; 2],
+        draws: 0,
+    };
+
+    for _ in 0..rounds {
+        let first_hand = first.play(&first_history);
+        let second_hand = second.play(&second_history);
+
+        result.scores[0] += first_hand.play_against(&second_hand);
+        result.scores[1] += second_hand.play_against(&first_hand);
+        match first_hand.cmp(&second_hand) {
+            Ordering::Greater => result.wins[0] += 1,
+            Ordering::Less => result.wins[1] += 1,
+            Ordering::Equal => result.draws += 1,
+        }
+
+        first_history.push(Round {
+            ours: first_hand,
+            theirs: second_hand,
+        });
+        second_history.push(Round {
+            ours: second_hand,
+            theirs: first_hand,
+        });
+    }
+
+    result
+}
+
+/// A round-robin bracket where every entrant plays every other entrant once.
+pub struct Tournament {
+    rounds_per_match: usize,
+    entrants: Vec<Box<dyn Strategy>>,
+}
+
+impl Tournament {
+    pub fn new(rounds_per_match: usize) -> Self {
+        Self {
+            rounds_per_match,
+            entrants: Vec::new(),
+        }
+    }
+
+    pub fn enter(&mut self, strategy: impl Strategy + 'static) -> &mut Self {
+        self.entrants.push(Box::new(strategy));
+        self
+    }
+
+    pub fn round_robin(&mut self) -> TournamentResults {
+        let mut matches = Vec::new();
+        for first in 0..self.entrants.len() {
+            for second in first + 1..self.entrants.len() {
+                // Need two mutable entrants at once.
+                let (left, right) = self.entrants.split_at_mut(second);
+                let mut result =
+                    play_match(&mut *left[first], &mut *right[0], self.rounds_per_match);
+                result.players = [first, second];
+                matches.push(result);
+            }
+        }
+
+        TournamentResults {
+            names: self
+                .entrants
+                .iter()
+                .map(|entrant| entrant.name().to_string())
+                .collect(),
+            matches,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TournamentResults {
+    pub names: Vec<String>,
+    pub matches: Vec<MatchResult>,
+}
+
+/// One line of the leaderboard. Match counts are whole matches, not rounds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Standing {
+    pub name: String,
+    pub total_score: u64,
+    pub match_wins: u64,
+    pub match_losses: u64,
+    pub match_draws: u64,
+}
+
+impl TournamentResults {
+    /// `table[i][j]` is the score player `i` got against player `j`.
+    pub fn score_table(&self) -> Vec<Vec<u64>> {
+        let mut table = vec![vec![0; self.names.len()]; self.names.len()];
+        for result in &self.matches {
+            let [a, b] = result.players;
+            table[a][b] = result.scores[0];
+            table[b][a] = result.scores[1];
+        }
+        table
+    }
+
+    /// Players sorted by match wins and then total score, best first.
+    pub fn leaderboard(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .names
+            .iter()
+            .map(|name| Standing {
+                name: name.clone(),
+                total_score: 0,
+                match_wins: 0,
+                match_losses: 0,
+                match_draws: 0,
+            })
+            .collect();
+
+        for result in &self.matches {
+            for (player, score) in result.players.iter().zip(result.scores) {
+                standings[*player].total_score += score;
+            }
+            match result.winner() {
+                Some(winner) => {
+                    let loser = if winner == result.players[0] {
+                        result.players[1]
+                    } else {
+                        result.players[0]
+                    };
+                    standings[winner].match_wins += 1;
+                    standings[loser].match_losses += 1;
+                }
+                None => {
+                    standings[result.players[0]].match_draws += 1;
+                    standings[result.players[1]].match_draws += 1;
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| {
+            b.match_wins
+                .cmp(&a.match_wins)
+                .then(b.total_score.cmp(&a.total_score))
+        });
+        standings
+    }
+}
+
+impl fmt::Display for TournamentResults {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.names.iter().map(String::len).max().unwrap_or(0);
+        for (rank, standing) in self.leaderboard().iter().enumerate() {
+            writeln!(
+                f,
+                "{:>2}. {:<width$}  {:>3}W {:>3}L {:>3}D  {:>8}",
+                rank + 1,
+                standing.name,
+                standing.match_wins,
+                standing.match_losses,
+                standing.match_draws,
+                standing.total_score,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use RockPaperScissors::*;
+
+    #[test]
+    fn fixed_sequence_loops() {
+        let mut strategy = FixedSequence::new("rps", vec![Rock, Paper]);
+        let round = Round {
+            ours: Rock,
+            theirs: Rock,
+        };
+
+        assert_eq!(strategy.play(&[]), Rock);
+        assert_eq!(strategy.play(&[round]), Paper);
+        assert_eq!(strategy.play(&[round, round]), Rock);
+    }
+
+    #[test]
+    fn fixed_sequence_from_guide() {
+        let guide = "A Y\nB X\nC Z\n";
+
+        let mut ours = FixedSequence::from_guide("ours", guide).unwrap();
+        let mut theirs = FixedSequence::opponent_from_guide("theirs", guide).unwrap();
+        let result = play_match(&mut ours, &mut theirs, 3);
+
+        // Same as part 1 of the example.
+        assert_eq!(result.scores[0], 15);
+    }
+
+    #[test]
+    fn fixed_sequence_from_empty_guide() {
+        assert_eq!(
+            FixedSequence::from_guide("ours", "").unwrap_err(),
+            GuideStrategyError::Empty
+        );
+        assert_eq!(
+            FixedSequence::opponent_from_guide("theirs", "\n\n").unwrap_err(),
+            GuideStrategyError::Empty
+        );
+        assert!(matches!(
+            FixedSequence::from_guide("ours", "A Q\n"),
+            Err(GuideStrategyError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn frequency_counter_beats_constant() {
+        let mut rock = FixedSequence::new("rock", vec![Rock]);
+        let result = play_match(&mut FrequencyCounter, &mut rock, 100);
+
+        assert_eq!(result.wins, [100, 0]);
+        assert_eq!(result.winner(), Some(0));
+    }
+
+    #[test]
+    fn pattern_matcher_beats_cycle() {
+        let mut cycle = FixedSequence::new("cycle", vec![Rock, Paper, Scissors]);
+        let mut matcher = PatternMatcher::new(2);
+        let result = play_match(&mut matcher, &mut cycle, 300);
+
+        assert!(result.wins[0] > 290, "{result:?}");
+    }
+
+    #[test]
+    fn seeded_random_is_repeatable() {
+        let mut first = SeededRandom::new(42);
+        let mut second = SeededRandom::new(42);
+        let first_result = play_match(&mut first, &mut FrequencyCounter, 50);
+        let second_result = play_match(&mut second, &mut FrequencyCounter, 50);
+
+        assert_eq!(first_result, second_result);
+
+        // Resetting should replay the same hands.
+        assert_eq!(
+            play_match(&mut first, &mut FrequencyCounter, 50),
+            first_result
+        );
+    }
+
+    #[test]
+    fn round_robin() {
+        let mut tournament = Tournament::new(30);
+        tournament
+            .enter(FixedSequence::new("rock", vec![Rock]))
+            .enter(FrequencyCounter)
+            .enter(FixedSequence::new("paper", vec![Paper]));
+        let results = tournament.round_robin();
+
+        assert_eq!(results.matches.len(), 3);
+
+        let table = results.score_table();
+        assert_eq!(table[0][2], 30);
+        assert_eq!(table[2][0], 30 * 8);
+
+        let leaderboard = results.leaderboard();
+        assert_eq!(leaderboard[0].name, "frequency counter");
+        assert_eq!(leaderboard[0].match_wins, 2);
+        assert_eq!(leaderboard[2].name, "rock");
+        assert_eq!(leaderboard[2].match_losses, 2);
+    }
+}
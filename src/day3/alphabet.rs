@@ -0,0 +1,195 @@
+use std::fmt;
+
+/// Describes which items can go in a rucksack and how important each one is.
+///
+/// Every valid item gets a unique slot in `0..slot_count` so sets of items can be stored as bitsets.
+/// Alphabets with no upper bound (`slot_count() == None`) fall back to a sparse set.
+pub trait Alphabet {
+    /// The number of slots, if bounded.
+    fn slot_count(&self) -> Option<usize>;
+
+    /// The slot for `item` or `None` if it isn't in the alphabet.
+    fn slot(&self, item: char) -> Option<usize>;
+
+    /// The inverse of `slot`.
+    fn item(&self, slot: usize) -> char;
+
+    /// The priority of the item in `slot`.
+    fn priority(&self, slot: usize) -> u64;
+}
+
+/// The puzzle's alphabet: `a` through `z` have priorities 1 through 26 and `A` through `Z` have
+/// priorities 27 through 52. Slots and priorities are the same thing here.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Letters;
+
+impl Alphabet for Letters {
+    fn slot_count(&self) -> Option<usize> {
+        Some(53)
+    }
+
+    fn slot(&self, item: char) -> Option<usize> {
+        let priority = match item {
+            'a'..='z' => item as u8 - b'a' + 1,
+            'A'..='Z' => item as u8 - b'A' + 27,
+            _ => return None,
+        };
+        Some(priority as usize)
+    }
+
+    fn item(&self, slot: usize) -> char {
+        let slot = slot as u8;
+        if slot <= 26 {
+            (slot - 1 + b'a') as char
+        } else {
+            (slot - 27 + b'A') as char
+        }
+    }
+
+    fn priority(&self, slot: usize) -> u64 {
+        slot as u64
+    }
+}
+
+/// Items are the Latin-1 characters, U+0000 through U+00FF, prioritised by a user-defined function
+/// of their code point. This isn't the bytes of the line: `'é'` is one item with code point `0xE9`
+/// even though it's two bytes of UTF-8. Characters the function returns `None` for, and anything
+/// past U+00FF, aren't part of the alphabet.
+#[derive(Clone, Copy)]
+pub struct Latin1<F> {
+    priority: F,
+}
+
+impl<F> Latin1<F>
+where
+    F: Fn(u8) -> Option<u64>,
+{
+    pub fn new(priority: F) -> Self {
+        Self { priority }
+    }
+}
+
+impl<F> Alphabet for Latin1<F>
+where
+    F: Fn(u8) -> Option<u64>,
+{
+    fn slot_count(&self) -> Option<usize> {
+        Some(256)
+    }
+
+    fn slot(&self, item: char) -> Option<usize> {
+        let code_point = u8::try_from(item).ok()?;
+        (self.priority)(code_point)?;
+        Some(code_point as usize)
+    }
+
+    fn item(&self, slot: usize) -> char {
+        slot as u8 as char
+    }
+
+    fn priority(&self, slot: usize) -> u64 {
+        (self.priority)(slot as u8).expect("Slot came from a valid item")
+    }
+}
+
+/// Items are any Unicode scalar value prioritised by a user-defined function. Chars the function
+/// returns `None` for aren't part of the alphabet.
+#[derive(Clone, Copy)]
+pub struct Unicode<F> {
+    priority: F,
+}
+
+impl<F> Unicode<F>
+where
+    F: Fn(char) -> Option<u64>,
+{
+    pub fn new(priority: F) -> Self {
+        Self { priority }
+    }
+}
+
+impl<F> Alphabet for Unicode<F>
+where
+    F: Fn(char) -> Option<u64>,
+{
+    fn slot_count(&self) -> Option<usize> {
+        None
+    }
+
+    fn slot(&self, item: char) -> Option<usize> {
+        (self.priority)(item)?;
+        Some(item as usize)
+    }
+
+    fn item(&self, slot: usize) -> char {
+        char::from_u32(slot as u32).expect("Slot came from a valid char")
+    }
+
+    fn priority(&self, slot: usize) -> u64 {
+        (self.priority)(self.item(slot)).expect("Slot came from a valid item")
+    }
+}
+
+/// An item that isn't part of the alphabet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidItem {
+    /// 1 indexed.
+    pub line: usize,
+    pub item: char,
+}
+
+impl fmt::Display for InvalidItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {:?} isn't in the alphabet",
+            self.line, self.item
+        )
+    }
+}
+
+impl std::error::Error for InvalidItem {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters() {
+        assert_eq!(Letters.slot('a'), Some(1));
+        assert_eq!(Letters.slot('z'), Some(26));
+        assert_eq!(Letters.slot('A'), Some(27));
+        assert_eq!(Letters.slot('Z'), Some(52));
+        assert_eq!(Letters.slot('1'), None);
+
+        for slot in 1..=52 {
+            assert_eq!(Letters.slot(Letters.item(slot)), Some(slot));
+        }
+    }
+
+    #[test]
+    fn latin1() {
+        let alphabet = Latin1::new(|b| b.is_ascii_graphic().then_some(b as u64));
+
+        assert_eq!(alphabet.slot('#'), Some(b'#' as usize));
+        assert_eq!(alphabet.slot(' '), None);
+        assert_eq!(alphabet.slot('é'), None);
+        assert_eq!(alphabet.priority(b'#' as usize), b'#' as u64);
+        assert_eq!(alphabet.item(b'#' as usize), '#');
+
+        let alphabet = Latin1::new(|b| Some(b as u64));
+        assert_eq!(alphabet.slot('é'), Some(0xE9));
+        assert_eq!(alphabet.item(0xE9), 'é');
+        assert_eq!(alphabet.slot('😀'), None);
+    }
+
+    #[test]
+    fn unicode() {
+        let alphabet = Unicode::new(|c| (!c.is_whitespace()).then_some(c as u64 % 100));
+
+        let slot = alphabet.slot('😀').unwrap();
+        assert_eq!(alphabet.item(slot), '😀');
+        assert_eq!(alphabet.priority(slot), '😀' as u64 % 100);
+        assert_eq!(alphabet.slot(' '), None);
+    }
+}
@@ -0,0 +1,160 @@
+use super::alphabet::{Alphabet, InvalidItem};
+use super::item_set::ItemSet;
+
+/// What we found when looking for a group's badge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BadgeStatus {
+    /// Exactly one item is in every rucksack, like the puzzle promises.
+    Found { item: char, priority: u64 },
+    /// No item is in every rucksack.
+    NoCommonItem,
+    /// More than one item is in every rucksack so we can't tell which is the badge.
+    Ambiguous,
+    /// The input ran out before the group was full.
+    Incomplete,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupReport {
+    /// 1 indexed line numbers of each elf in the group.
+    pub lines: Vec<usize>,
+    /// Every item in all of the group's rucksacks, in slot order.
+    pub shared: Vec<char>,
+    pub status: BadgeStatus,
+}
+
+/// Splits the rucksacks into groups of `group_size` elves and reports on each group's badge.
+///
+/// Only items outside of the alphabet are errors. Anything wrong with the groups themselves is
+/// recorded in `GroupReport::status`.
+pub fn audit_groups<A: Alphabet>(
+    input: &str,
+    alphabet: &A,
+    group_size: usize,
+) -> Result<Vec<GroupReport>, InvalidItem> {
+    assert!(group_size > 0, "Groups need at least one elf");
+
+    with_item_set!(alphabet, audit(input, alphabet, group_size))
+}
+
+fn audit<A, S>(
+    input: &str,
+    alphabet: &A,
+    group_size: usize,
+) -> Result<Vec<GroupReport>, InvalidItem>
+where
+    A: Alphabet,
+    S: ItemSet,
+{
+    let lines: Vec<_> = input.lines().enumerate().collect();
+
+    lines
+        .chunks(group_size)
+        .map(|group| {
+            let mut shared: Option<S> = None;
+            for (idx, line) in group {
+                let mut rucksack = S::default();
+                for item in line.chars() {
+                    let slot = alphabet.slot(item).ok_or(InvalidItem {
+                        line: idx + 1,
+                        item,
+                    })?;
+                    rucksack.insert(slot);
+                }
+
+                match &mut shared {
+                    Some(shared) => shared.intersect_with(&rucksack),
+                    None => shared = Some(rucksack),
+                }
+            }
+
+            // `chunks` never gives us an empty group.
+            let shared = shared.unwrap_or_default();
+            let status = if group.len() < group_size {
+                BadgeStatus::Incomplete
+            } else {
+                match shared.single() {
+                    Some(slot) => BadgeStatus::Found {
+                        item: alphabet.item(slot),
+                        priority: alphabet.priority(slot),
+                    },
+                    None if shared.slots().next().is_none() => BadgeStatus::NoCommonItem,
+                    None => BadgeStatus::Ambiguous,
+                }
+            };
+
+            Ok(GroupReport {
+                lines: group.iter().map(|(idx, _)| idx + 1).collect(),
+                shared: shared.slots().map(|slot| alphabet.item(slot)).collect(),
+                status,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::alphabet::Letters;
+    use super::*;
+
+    #[test]
+    fn found() {
+        let report = audit_groups("abc\nbcd\ncef\n", &Letters, 3).unwrap();
+
+        assert_eq!(
+            report,
+            [GroupReport {
+                lines: vec![1, 2, 3],
+                shared: vec!['c'],
+                status: BadgeStatus::Found {
+                    item: 'c',
+                    priority: 3
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn configurable_group_size() {
+        let report = audit_groups("aZ\nbZ\ncb\ndb\n", &Letters, 2).unwrap();
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].lines, [1, 2]);
+        assert_eq!(report[0].shared, ['Z']);
+        assert_eq!(report[1].lines, [3, 4]);
+        assert_eq!(report[1].shared, ['b']);
+    }
+
+    #[test]
+    fn no_common_item() {
+        let report = audit_groups("ab\ncd\n", &Letters, 2).unwrap();
+
+        assert_eq!(report[0].shared, []);
+        assert_eq!(report[0].status, BadgeStatus::NoCommonItem);
+    }
+
+    #[test]
+    fn ambiguous() {
+        let report = audit_groups("abc\nbcd\n", &Letters, 2).unwrap();
+
+        assert_eq!(report[0].shared, ['b', 'c']);
+        assert_eq!(report[0].status, BadgeStatus::Ambiguous);
+    }
+
+    #[test]
+    fn incomplete() {
+        let report = audit_groups("ab\nab\nab\nab\n", &Letters, 3).unwrap();
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[1].lines, [4]);
+        assert_eq!(report[1].shared, ['a', 'b']);
+        assert_eq!(report[1].status, BadgeStatus::Incomplete);
+    }
+
+    #[test]
+    fn invalid_item() {
+        let err = audit_groups("ab\na?\n", &Letters, 2).unwrap_err();
+
+        assert_eq!(err, InvalidItem { line: 2, item: '?' });
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use super::alphabet::{Alphabet, InvalidItem};
+use super::item_set::ItemSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compartment {
+    First,
+    Second,
+}
+
+/// An item type found in both compartments. Positions are 1 indexed columns in the line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedItem {
+    pub item: char,
+    pub priority: u64,
+    pub first: Vec<usize>,
+    pub second: Vec<usize>,
+}
+
+/// Moving the item at `position` out of `from` and into the other compartment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub item: char,
+    pub position: usize,
+    pub from: Compartment,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RucksackReport {
+    /// 1 indexed.
+    pub line: usize,
+    /// The compartments are supposed to be the same size. If they can't be, the first compartment
+    /// gets the smaller half.
+    pub odd_length: bool,
+    /// Every item type in both compartments, in slot order.
+    pub shared: Vec<SharedItem>,
+    /// The fewest single-item moves that leave no item type in both compartments. Each shared
+    /// type is moved out of whichever compartment has fewer of it (the second on ties) so the
+    /// compartments may not be the same size afterwards.
+    pub moves: Vec<Move>,
+}
+
+impl RucksackReport {
+    /// The puzzle expects exactly one shared item type and an even number of items.
+    pub fn is_expected(&self) -> bool {
+        !self.odd_length && self.shared.len() == 1
+    }
+}
+
+/// Reports on every rucksack rather than just the first duplicated item like `part1`.
+pub fn diagnose<A: Alphabet>(
+    input: &str,
+    alphabet: &A,
+) -> Result<Vec<RucksackReport>, InvalidItem> {
+    with_item_set!(alphabet, diagnose_with(input, alphabet))
+}
+
+fn diagnose_with<A, S>(input: &str, alphabet: &A) -> Result<Vec<RucksackReport>, InvalidItem>
+where
+    A: Alphabet,
+    S: ItemSet,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let slots = line
+                .chars()
+                .map(|item| {
+                    alphabet.slot(item).ok_or(InvalidItem {
+                        line: idx + 1,
+                        item,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let half = slots.len() / 2;
+            let (first, second) = slots.split_at(half);
+
+            let mut shared = S::default();
+            first.iter().for_each(|slot| shared.insert(*slot));
+            let mut second_set = S::default();
+            second.iter().for_each(|slot| second_set.insert(*slot));
+            shared.intersect_with(&second_set);
+
+            let mut shared_items = BTreeMap::new();
+            for slot in shared.slots() {
+                shared_items.insert(
+                    slot,
+                    SharedItem {
+                        item: alphabet.item(slot),
+                        priority: alphabet.priority(slot),
+                        first: Vec::new(),
+                        second: Vec::new(),
+                    },
+                );
+            }
+            for (position, slot) in slots.iter().enumerate() {
+                if let Some(shared_item) = shared_items.get_mut(slot) {
+                    let positions = if position < half {
+                        &mut shared_item.first
+                    } else {
+                        &mut shared_item.second
+                    };
+                    positions.push(position + 1);
+                }
+            }
+
+            let shared: Vec<_> = shared_items.into_values().collect();
+            let moves = shared.iter().flat_map(minimal_moves).collect();
+
+            Ok(RucksackReport {
+                line: idx + 1,
+                odd_length: slots.len() % 2 == 1,
+                shared,
+                moves,
+            })
+        })
+        .collect()
+}
+
+fn minimal_moves(shared: &SharedItem) -> impl Iterator<Item = Move> + '_ {
+    let (from, positions) = if shared.first.len() < shared.second.len() {
+        (Compartment::First, &shared.first)
+    } else {
+        (Compartment::Second, &shared.second)
+    };
+
+    positions.iter().map(move |position| Move {
+        item: shared.item,
+        position: *position,
+        from,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::alphabet::Letters;
+    use super::*;
+
+    #[test]
+    fn example_line() {
+        let report = diagnose("vJrwpWtwJgWrhcsFMMfFFhFp", &Letters).unwrap();
+
+        assert_eq!(report.len(), 1);
+        assert!(report[0].is_expected());
+        assert_eq!(
+            report[0].shared,
+            [SharedItem {
+                item: 'p',
+                priority: 16,
+                first: vec![5],
+                second: vec![24],
+            }]
+        );
+        assert_eq!(
+            report[0].moves,
+            [Move {
+                item: 'p',
+                position: 24,
+                from: Compartment::Second,
+            }]
+        );
+    }
+
+    #[test]
+    fn every_shared_item() {
+        let report = diagnose("aabcab\nabba\n", &Letters).unwrap();
+
+        let first = &report[0];
+        assert_eq!(first.line, 1);
+        assert!(!first.is_expected());
+        assert_eq!(first.shared.len(), 2);
+        assert_eq!(first.shared[0].item, 'a');
+        assert_eq!(first.shared[0].first, [1, 2]);
+        assert_eq!(first.shared[0].second, [5]);
+        assert_eq!(first.shared[1].item, 'b');
+        assert_eq!(first.shared[1].first, [3]);
+        assert_eq!(first.shared[1].second, [6]);
+        assert_eq!(
+            first.moves,
+            [
+                Move {
+                    item: 'a',
+                    position: 5,
+                    from: Compartment::Second,
+                },
+                Move {
+                    item: 'b',
+                    position: 6,
+                    from: Compartment::Second,
+                },
+            ]
+        );
+
+        // Both item types are in both compartments.
+        let second = &report[1];
+        assert_eq!(second.line, 2);
+        assert_eq!(second.moves.len(), 2);
+    }
+
+    #[test]
+    fn fewer_in_first_compartment() {
+        let report = diagnose("abbb", &Letters).unwrap();
+
+        assert_eq!(
+            report[0].moves,
+            [Move {
+                item: 'b',
+                position: 2,
+                from: Compartment::First,
+            }]
+        );
+    }
+
+    #[test]
+    fn odd_length() {
+        let report = diagnose("abcda", &Letters).unwrap();
+
+        assert!(report[0].odd_length);
+        assert_eq!(report[0].shared[0].first, [1]);
+        assert_eq!(report[0].shared[0].second, [5]);
+    }
+
+    #[test]
+    fn nothing_shared() {
+        let report = diagnose("abcd", &Letters).unwrap();
+
+        assert!(report[0].shared.is_empty());
+        assert!(report[0].moves.is_empty());
+        assert!(!report[0].is_expected());
+    }
+
+    #[test]
+    fn invalid_item() {
+        let err = diagnose("abab\nab-ab\n", &Letters).unwrap_err();
+
+        assert_eq!(err, InvalidItem { line: 2, item: '-' });
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+
+/// A set of alphabet slots (see `Alphabet::slot`). Different sizes of alphabet get different
+/// backing storage so the puzzle's alphabet can keep using a single `u64`.
+pub(super) trait ItemSet: Clone + Default {
+    fn insert(&mut self, slot: usize);
+
+    fn contains(&self, slot: usize) -> bool;
+
+    fn intersect_with(&mut self, other: &Self);
+
+    fn slots(&self) -> impl Iterator<Item = usize> + '_;
+
+    /// The only slot in the set, if there's exactly one.
+    fn single(&self) -> Option<usize> {
+        let mut slots = self.slots();
+        let slot = slots.next()?;
+        slots.next().is_none().then_some(slot)
+    }
+}
+
+/// For alphabets with at most 64 slots, like the puzzle's `a-zA-Z`.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct PriorityMask(u64);
+
+impl ItemSet for PriorityMask {
+    fn insert(&mut self, slot: usize) {
+        self.0 |= 1 << slot;
+    }
+
+    fn contains(&self, slot: usize) -> bool {
+        self.0 & 1 << slot > 0
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.0 &= other.0;
+    }
+
+    fn slots(&self) -> impl Iterator<Item = usize> + '_ {
+        bits(self.0, 0)
+    }
+
+    fn single(&self) -> Option<usize> {
+        self.0
+            .is_power_of_two()
+            .then(|| self.0.trailing_zeros() as usize)
+    }
+}
+
+/// For alphabets with at most 256 slots, e.g. every Latin-1 character.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct ByteSet([u64; 4]);
+
+impl ItemSet for ByteSet {
+    fn insert(&mut self, slot: usize) {
+        self.0[slot / 64] |= 1 << (slot % 64);
+    }
+
+    fn contains(&self, slot: usize) -> bool {
+        self.0[slot / 64] & 1 << (slot % 64) > 0
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        for (word, other) in self.0.iter_mut().zip(other.0) {
+            *word &= other;
+        }
+    }
+
+    fn slots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, word)| bits(*word, idx * 64))
+    }
+}
+
+/// For alphabets that are too big for a bitset, e.g. all of Unicode.
+#[derive(Clone, Debug, Default)]
+pub(super) struct SparseSet(BTreeSet<usize>);
+
+impl ItemSet for SparseSet {
+    fn insert(&mut self, slot: usize) {
+        self.0.insert(slot);
+    }
+
+    fn contains(&self, slot: usize) -> bool {
+        self.0.contains(&slot)
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.0.retain(|slot| other.0.contains(slot));
+    }
+
+    fn slots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+/// Indices of the set bits in `word`, lowest first, offset by `base`.
+fn bits(mut word: u64, base: usize) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let bit = word.trailing_zeros() as usize;
+        word &= word - 1;
+        Some(base + bit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<S: ItemSet>(slots: &[usize]) {
+        let mut set = S::default();
+        for slot in slots {
+            set.insert(*slot);
+        }
+        assert!(slots.iter().all(|slot| set.contains(*slot)));
+        assert_eq!(set.slots().collect::<Vec<_>>(), slots);
+        assert_eq!(set.single(), None);
+
+        let mut other = S::default();
+        other.insert(slots[1]);
+        set.intersect_with(&other);
+        assert_eq!(set.single(), Some(slots[1]));
+    }
+
+    #[test]
+    fn priority_mask() {
+        check::<PriorityMask>(&[0, 1, 27, 63]);
+    }
+
+    #[test]
+    fn byte_set() {
+        check::<ByteSet>(&[0, 63, 64, 200, 255]);
+    }
+
+    #[test]
+    fn sparse_set() {
+        check::<SparseSet>(&[0, 255, 1_000, 0x1F600]);
+    }
+}
@@ -1,95 +1,149 @@
-pub fn part1(input: &str) -> u64 {
-    input
-        .lines()
-        .map(|line| {
-            let line = line.as_bytes();
+use alphabet::{Alphabet, InvalidItem, Letters};
+use std::fmt;
+
+use badges::BadgeStatus;
+use item_set::ItemSet;
+
+/// Calls `f::<_, S>(args)` where `S` is the smallest `ItemSet` that fits `alphabet`.
+macro_rules! with_item_set {
+    ($alphabet:expr, $f:ident($($arg:expr),* $(,)?)) => {{
+        use $crate::day3::item_set::{ByteSet, PriorityMask, SparseSet};
+        match $alphabet.slot_count() {
+            Some(len) if len <= 64 => $f::<_, PriorityMask>($($arg),*),
+            Some(len) if len <= 256 => $f::<_, ByteSet>($($arg),*),
+            _ => $f::<_, SparseSet>($($arg),*),
+        }
+    }};
+}
 
-            let mut scratchpad = PriorityMask::default();
+pub mod alphabet;
+pub mod badges;
+pub mod compartments;
+mod item_set;
 
-            // TODO: The problem makes it sounds like this is always even but we should check.
-            let half = line.len() / 2;
-            for byte in line.iter().take(half) {
-                scratchpad.record_item(&PriorityMask::from(*byte));
-            }
+pub fn part1(input: &str) -> u64 {
+    part1_with(input, &Letters).unwrap_or_else(|err| panic!("Invalid rucksack: {err}"))
+}
 
-            line.iter()
-                .skip(half)
-                .map(|byte| PriorityMask::from(*byte))
-                .find(|priority| scratchpad.contains(priority))
-                .unwrap_or_else(|| panic!("No duplicate found in {line:?}"))
-                .0
-        })
-        .sum()
+/// Like `part1` but with a custom alphabet.
+pub fn part1_with<A: Alphabet>(input: &str, alphabet: &A) -> Result<u64, RucksackError> {
+    with_item_set!(alphabet, compartment_priorities(input, alphabet))
 }
 
-#[derive(Clone, Copy, Default)]
-struct PriorityMask(u64);
-impl From<u8> for PriorityMask {
-    fn from(b: u8) -> Self {
-        // This only covers the bytes we expect from this problem
-        let priority = if b <= b'Z' {
-            b - b'A' + 27
-        } else {
-            b - b'a' + 1
+fn compartment_priorities<A, S>(input: &str, alphabet: &A) -> Result<u64, RucksackError>
+where
+    A: Alphabet,
+    S: ItemSet,
+{
+    let mut priority_sum = 0;
+    for (idx, line) in input.lines().enumerate() {
+        let slot = |item| {
+            alphabet.slot(item).ok_or(InvalidItem {
+                line: idx + 1,
+                item,
+            })
         };
-        Self(priority as u64)
-    }
-}
-impl PriorityMask {
-    fn record_item(&mut self, other: &Self) {
-        self.0 |= 1 << other.0;
-    }
 
-    fn contains(&self, other: &Self) -> bool {
-        self.0 & 1 << other.0 > 0
-    }
+        let mut scratchpad = S::default();
+
+        // The problem makes it sounds like this is always even. `compartments::diagnose` reports
+        // the lines where it isn't - here the first compartment just gets the smaller half.
+        let half = line.chars().count() / 2;
+        for item in line.chars().take(half) {
+            scratchpad.insert(slot(item)?);
+        }
 
-    fn badge_from<I>(priorities: I) -> Option<u64>
-    where
-        I: IntoIterator<Item = Self>,
-    {
-        let single_item = priorities
-            .into_iter()
-            .map(|priority| priority.0)
-            .fold(!0, |acc, priority| acc & priority);
-
-        if single_item == 0 || !single_item.is_power_of_two() {
-            return None;
+        let mut duplicate = None;
+        for item in line.chars().skip(half) {
+            let item = slot(item)?;
+            if scratchpad.contains(item) {
+                duplicate = Some(item);
+                break;
+            }
         }
 
-        // Waiting on `u64::ilog2`...
-        let priority = (single_item - 1).count_ones() as u64;
-        Some(priority)
+        let duplicate = duplicate.ok_or(RucksackError::NoDuplicate { line: idx + 1 })?;
+        priority_sum += alphabet.priority(duplicate);
     }
+
+    Ok(priority_sum)
 }
 
 pub fn part2(input: &str) -> u64 {
-    let mut lines = input.lines();
+    part2_with(input, &Letters, 3).unwrap_or_else(|err| panic!("Invalid rucksack: {err}"))
+}
+
+/// Like `part2` but with a custom alphabet and group size.
+pub fn part2_with<A: Alphabet>(
+    input: &str,
+    alphabet: &A,
+    group_size: usize,
+) -> Result<u64, RucksackError> {
     let mut priority_sum = 0;
+    for group in badges::audit_groups(input, alphabet, group_size)? {
+        priority_sum += match group.status {
+            BadgeStatus::Found { priority, .. } => priority,
+            BadgeStatus::NoCommonItem => return Err(RucksackError::NoBadge { lines: group.lines }),
+            BadgeStatus::Ambiguous => {
+                return Err(RucksackError::AmbiguousBadge { lines: group.lines })
+            }
+            BadgeStatus::Incomplete => {
+                return Err(RucksackError::IncompleteGroup { lines: group.lines })
+            }
+        };
+    }
 
-    while let Some(line) = lines.next() {
-        let mut rucksacks = [PriorityMask::default(); 3];
-        for byte in line.as_bytes() {
-            let priority = PriorityMask::from(*byte);
-            rucksacks[0].record_item(&priority);
-        }
+    Ok(priority_sum)
+}
 
-        // Get the two other elves in this group (we'll _assume_ :warning: they're there)
-        // and remove their items from the list until there's only one left.
-        for rucksack in rucksacks.iter_mut().skip(1) {
-            let line = lines.next().expect("Elves should be in groups of three!");
-            for byte in line.as_bytes() {
-                let priority = PriorityMask::from(*byte);
-                rucksack.record_item(&priority);
-            }
-        }
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RucksackError {
+    InvalidItem(InvalidItem),
+    /// Nothing is in both compartments of the rucksack on this line (1 indexed).
+    NoDuplicate {
+        line: usize,
+    },
+    /// No item is in every rucksack of the group on these lines.
+    NoBadge {
+        lines: Vec<usize>,
+    },
+    /// More than one item is in every rucksack of the group on these lines.
+    AmbiguousBadge {
+        lines: Vec<usize>,
+    },
+    /// The input ran out before the last group was full.
+    IncompleteGroup {
+        lines: Vec<usize>,
+    },
+}
 
-        priority_sum += PriorityMask::badge_from(rucksacks).unwrap();
+impl From<InvalidItem> for RucksackError {
+    fn from(err: InvalidItem) -> Self {
+        Self::InvalidItem(err)
     }
+}
 
-    priority_sum
+impl fmt::Display for RucksackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidItem(err) => err.fmt(f),
+            Self::NoDuplicate { line } => {
+                write!(f, "line {line}: no item is in both compartments")
+            }
+            Self::NoBadge { lines } => write!(f, "lines {lines:?}: no item is in every rucksack"),
+            Self::AmbiguousBadge { lines } => {
+                write!(
+                    f,
+                    "lines {lines:?}: more than one item is in every rucksack"
+                )
+            }
+            Self::IncompleteGroup { lines } => write!(f, "lines {lines:?}: the group isn't full"),
+        }
+    }
 }
 
+impl std::error::Error for RucksackError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,6 +165,46 @@ mod tests {
             assert_eq!(part1(EXAMPLE), 157);
         }
 
+        #[test]
+        fn example_with_every_set() {
+            let latin1 = alphabet::Latin1::new(|b| Letters.slot(b as char).map(|p| p as u64));
+            let unicode = alphabet::Unicode::new(|c| Letters.slot(c).map(|p| p as u64));
+
+            assert_eq!(part1_with(EXAMPLE, &latin1), Ok(157));
+            assert_eq!(part1_with(EXAMPLE, &unicode), Ok(157));
+        }
+
+        #[test]
+        fn warehouse_codes() {
+            // Digits are worth 100 more than their value and punctuation is worth 1.
+            let alphabet = alphabet::Latin1::new(|b| match b {
+                b'0'..=b'9' => Some((b - b'0') as u64 + 100),
+                b'!'..=b'/' => Some(1),
+                _ => None,
+            });
+
+            assert_eq!(part1_with("12#$7#\n90/9\n", &alphabet), Ok(1 + 109));
+        }
+
+        #[test]
+        fn invalid_item() {
+            assert_eq!(
+                part1_with("abab\nab1a\n", &Letters),
+                Err(RucksackError::InvalidItem(InvalidItem {
+                    line: 2,
+                    item: '1'
+                }))
+            );
+        }
+
+        #[test]
+        fn no_duplicate() {
+            assert_eq!(
+                part1_with("abab\nabcd\n", &Letters),
+                Err(RucksackError::NoDuplicate { line: 2 })
+            );
+        }
+
         #[test]
         fn my_input() {
             assert_eq!(part1(INPUT), 7863);
@@ -125,6 +219,33 @@ mod tests {
             assert_eq!(part2(EXAMPLE), 70);
         }
 
+        #[test]
+        fn unicode_items() {
+            let alphabet = alphabet::Unicode::new(|c| Some(c as u64));
+
+            assert_eq!(
+                part2_with("a😀b\nc😀\n😀dé\n", &alphabet, 3),
+                Ok('😀' as u64)
+            );
+        }
+
+        #[test]
+        fn bad_groups() {
+            assert_eq!(part2_with("aZ\nbZ\ncb\ndb\n", &Letters, 2), Ok(52 + 2));
+            assert_eq!(
+                part2_with("ab\nac\nad\nac\nbd\n", &Letters, 3),
+                Err(RucksackError::IncompleteGroup { lines: vec![4, 5] })
+            );
+            assert_eq!(
+                part2_with("ab\ncd\n", &Letters, 2),
+                Err(RucksackError::NoBadge { lines: vec![1, 2] })
+            );
+            assert_eq!(
+                part2_with("abc\nbcd\n", &Letters, 2),
+                Err(RucksackError::AmbiguousBadge { lines: vec![1, 2] })
+            );
+        }
+
         #[test]
         fn my_input() {
             assert_eq!(part2(INPUT), 2488);
@@ -0,0 +1,164 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::{parse_assignments, InvalidAssignment};
+use crate::interval_set::IntervalSet;
+
+/// What we know about one line of assignments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupAnalysis {
+    pub assignments: Vec<RangeInclusive<u64>>,
+    /// Indices (into `assignments`) of elves whose sections are all covered by the rest of the
+    /// group. Each one could be sent home on its own but, e.g., two elves with the same
+    /// assignment are both listed even though only one of them can go.
+    pub redundant: Vec<usize>,
+    /// The most elves assigned to any single section.
+    pub max_coverage: usize,
+    /// Sections in the camp nobody in the group is assigned to.
+    pub uncovered: IntervalSet<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1 indexed.
+    pub line: usize,
+    pub source: InvalidAssignment,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.source)
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Analyzes every line of the input, each of which can have any number of elves. The camp's
+/// sections are numbered `1..=camp_size`.
+pub fn analyze_groups(input: &str, camp_size: u64) -> Result<Vec<GroupAnalysis>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(idx, line)| {
+            let assignments = parse_assignments(line).map_err(|source| ParseError {
+                line: idx + 1,
+                source,
+            })?;
+            Ok(analyze_group(assignments, camp_size))
+        })
+        .collect()
+}
+
+pub fn analyze_group(assignments: Vec<RangeInclusive<u64>>, camp_size: u64) -> GroupAnalysis {
+    let redundant = (0..assignments.len())
+        .filter(|&elf| {
+            let everyone_else: IntervalSet<u64> = assignments
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != elf)
+                .map(|(_, assignment)| assignment.clone())
+                .collect();
+            everyone_else.contains_range(&assignments[elf])
+        })
+        .collect();
+
+    let everyone: IntervalSet<u64> = assignments.iter().cloned().collect();
+    let uncovered = everyone.complement(1..=camp_size);
+
+    GroupAnalysis {
+        redundant,
+        max_coverage: max_coverage(&assignments),
+        uncovered,
+        assignments,
+    }
+}
+
+fn max_coverage(assignments: &[RangeInclusive<u64>]) -> usize {
+    // Sweep over every start and end. Starts sort before ends on the same section because the
+    // ranges are inclusive so both elves are there.
+    let mut events: Vec<(u64, bool)> = assignments
+        .iter()
+        .flat_map(|assignment| [(*assignment.start(), false), (*assignment.end(), true)])
+        .collect();
+    events.sort_unstable();
+
+    let mut coverage = 0;
+    let mut max_coverage = 0;
+    for (_, is_end) in events {
+        if is_end {
+            coverage -= 1;
+        } else {
+            coverage += 1;
+            max_coverage = max_coverage.max(coverage);
+        }
+    }
+
+    max_coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs() {
+        let analysis = analyze_groups("2-8,3-7\n2-4,6-8\n", 9).unwrap();
+
+        assert_eq!(analysis[0].redundant, [1]);
+        assert_eq!(analysis[0].max_coverage, 2);
+        assert_eq!(
+            analysis[0].uncovered.iter().collect::<Vec<_>>(),
+            [1..=1, 9..=9]
+        );
+
+        assert_eq!(analysis[1].redundant, []);
+        assert_eq!(analysis[1].max_coverage, 1);
+        assert_eq!(
+            analysis[1].uncovered.iter().collect::<Vec<_>>(),
+            [1..=1, 5..=5, 9..=9]
+        );
+    }
+
+    #[test]
+    fn redundant_by_several_elves() {
+        let analysis = analyze_group(vec![1..=3, 4..=6, 2..=5, 7..=7], 10);
+
+        // Elf 2 is covered by elves 0 and 1 together but not by either one alone.
+        assert_eq!(analysis.redundant, [2]);
+        assert_eq!(analysis.max_coverage, 2);
+        assert_eq!(analysis.uncovered.iter().collect::<Vec<_>>(), [8..=10]);
+    }
+
+    #[test]
+    fn identical_elves() {
+        let analysis = analyze_group(vec![1..=3, 1..=3, 3..=3], 3);
+
+        assert_eq!(analysis.redundant, [0, 1, 2]);
+        assert_eq!(analysis.max_coverage, 3);
+        assert!(analysis.uncovered.is_empty());
+    }
+
+    #[test]
+    fn touching_isnt_overlapping() {
+        assert_eq!(max_coverage(&[1..=2, 3..=4, 5..=6]), 1);
+        assert_eq!(max_coverage(&[1..=2, 2..=4, 4..=6]), 2);
+    }
+
+    #[test]
+    fn invalid_assignment() {
+        let err = analyze_groups("1-2,3-4\n1-2,4-3\n", 10).unwrap_err();
+
+        assert_eq!(
+            err,
+            ParseError {
+                line: 2,
+                source: InvalidAssignment("4-3".to_string()),
+            }
+        );
+    }
+}
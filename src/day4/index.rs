@@ -0,0 +1,290 @@
+use std::ops::RangeInclusive;
+
+use super::groups::ParseError;
+use super::parse_assignments;
+
+/// Which elf an assignment belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssignmentId {
+    /// 1 indexed line of the input.
+    pub line: usize,
+    /// 0 indexed position in the line.
+    pub elf: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    start: u64,
+    end: u64,
+    id: AssignmentId,
+}
+
+/// Answers "who covers this section" style questions about lots of assignments.
+///
+/// Assignments are sorted by start and treated as an implicit balanced binary search tree (the
+/// middle of every slice is the root of that slice) where each node also knows the largest end in
+/// its subtree. Overlap queries skip any subtree that ends before the query starts and stop going
+/// right once assignments start after the query ends. Each result costs at most one path down the
+/// tree, so they take `O(min(n, k log n))` for `k` results.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageIndex {
+    entries: Vec<Entry>,
+    // `max_end[i]` is the largest end in the subtree rooted at `entries[i]`.
+    max_end: Vec<u64>,
+    // Sections where the number of elves changes and the new count from there on. The count
+    // before the first section is 0.
+    coverage: Vec<(u64, usize)>,
+}
+
+impl CoverageIndex {
+    pub fn new<I>(assignments: I) -> Self
+    where
+        I: IntoIterator<Item = (AssignmentId, RangeInclusive<u64>)>,
+    {
+        let mut entries: Vec<Entry> = assignments
+            .into_iter()
+            .map(|(id, range)| Entry {
+                start: *range.start(),
+                end: *range.end(),
+                id,
+            })
+            .filter(|entry| entry.start <= entry.end)
+            .collect();
+        entries.sort_unstable_by_key(|entry| (entry.start, entry.id));
+
+        let mut max_end = vec![0; entries.len()];
+        fill_max_end(&entries, &mut max_end, 0, entries.len());
+
+        let coverage = coverage_steps(&entries);
+
+        Self {
+            entries,
+            max_end,
+            coverage,
+        }
+    }
+
+    /// Indexes every assignment in the input.
+    pub fn from_input(input: &str) -> Result<Self, ParseError> {
+        let mut assignments = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let ranges = parse_assignments(line).map_err(|source| ParseError {
+                line: idx + 1,
+                source,
+            })?;
+            for (elf, range) in ranges.into_iter().enumerate() {
+                assignments.push((AssignmentId { line: idx + 1, elf }, range));
+            }
+        }
+
+        Ok(Self::new(assignments))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every assignment that includes `section`, sorted by ID.
+    pub fn covering(&self, section: u64) -> Vec<AssignmentId> {
+        self.overlapping(section..=section)
+    }
+
+    /// Every assignment that shares at least one section with `range`, sorted by ID.
+    pub fn overlapping(&self, range: RangeInclusive<u64>) -> Vec<AssignmentId> {
+        let mut found = Vec::new();
+        if !range.is_empty() {
+            self.search(&range, 0, self.entries.len(), &mut found);
+        }
+        found.sort_unstable();
+        found
+    }
+
+    /// The number of assignments that include `section`.
+    pub fn coverage(&self, section: u64) -> usize {
+        let idx = self
+            .coverage
+            .partition_point(|(start, _)| *start <= section);
+        idx.checked_sub(1).map_or(0, |idx| self.coverage[idx].1)
+    }
+
+    /// The coverage count for every section from the first assigned section to the last, as runs
+    /// of sections with the same count.
+    pub fn coverage_runs(&self) -> impl Iterator<Item = (RangeInclusive<u64>, usize)> + '_ {
+        let runs = self.coverage.windows(2).map(|pair| {
+            let (start, count) = pair[0];
+            let (next_start, _) = pair[1];
+            (start..=next_start - 1, count)
+        });
+        // Assignments that end at `u64::MAX` never step back down, so the last run goes to there.
+        let last = self
+            .coverage
+            .last()
+            .filter(|(_, count)| *count > 0)
+            .map(|&(start, count)| (start..=u64::MAX, count));
+        runs.chain(last)
+    }
+
+    fn search(
+        &self,
+        range: &RangeInclusive<u64>,
+        lo: usize,
+        hi: usize,
+        found: &mut Vec<AssignmentId>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+
+        // Nothing in this subtree reaches the query.
+        if self.max_end[mid] < *range.start() {
+            return;
+        }
+
+        self.search(range, lo, mid, found);
+
+        let entry = &self.entries[mid];
+        // Everything to the right starts even later.
+        if entry.start > *range.end() {
+            return;
+        }
+        if entry.end >= *range.start() {
+            found.push(entry.id);
+        }
+
+        self.search(range, mid + 1, hi, found);
+    }
+}
+
+fn fill_max_end(entries: &[Entry], max_end: &mut [u64], lo: usize, hi: usize) -> u64 {
+    if lo >= hi {
+        return 0;
+    }
+    let mid = lo + (hi - lo) / 2;
+    let left = fill_max_end(entries, max_end, lo, mid);
+    let right = fill_max_end(entries, max_end, mid + 1, hi);
+    max_end[mid] = entries[mid].end.max(left).max(right);
+    max_end[mid]
+}
+
+fn coverage_steps(entries: &[Entry]) -> Vec<(u64, usize)> {
+    // +1 when an assignment starts and -1 the section after it ends. `u64::MAX` can't have a
+    // section after it so those assignments never end.
+    let mut events: Vec<(u64, isize)> = Vec::with_capacity(entries.len() * 2);
+    for entry in entries {
+        events.push((entry.start, 1));
+        if let Some(after) = entry.end.checked_add(1) {
+            events.push((after, -1));
+        }
+    }
+    events.sort_unstable();
+
+    let mut steps: Vec<(u64, usize)> = Vec::new();
+    let mut count = 0;
+    for (section, delta) in events {
+        count = (count as isize + delta) as usize;
+        match steps.last_mut() {
+            Some(last) if last.0 == section => last.1 = count,
+            _ => steps.push((section, count)),
+        }
+    }
+
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    fn id(line: usize, elf: usize) -> AssignmentId {
+        AssignmentId { line, elf }
+    }
+
+    const EXAMPLE: &str = "2-4,6-8\n\
+                           2-3,4-5\n\
+                           5-7,7-9\n\
+                           2-8,3-7\n\
+                           6-6,4-6\n\
+                           2-6,4-8\n";
+
+    #[test]
+    fn example() {
+        let index = CoverageIndex::from_input(EXAMPLE).unwrap();
+
+        assert_eq!(index.len(), 12);
+        assert_eq!(index.covering(9), [id(3, 1)], "Only one elf goes up to 9");
+        assert_eq!(
+            index.covering(3),
+            [id(1, 0), id(2, 0), id(4, 0), id(4, 1), id(6, 0)]
+        );
+        assert_eq!(index.covering(1), []);
+        assert_eq!(
+            index.overlapping(8..=100),
+            [id(1, 1), id(3, 1), id(4, 0), id(6, 1)]
+        );
+
+        assert_eq!(index.coverage(1), 0);
+        assert_eq!(index.coverage(3), 5);
+        assert_eq!(index.coverage(6), 8);
+        assert_eq!(index.coverage(9), 1);
+        assert_eq!(index.coverage(10), 0);
+
+        let runs: Vec<_> = index.coverage_runs().collect();
+        assert_eq!(runs.first(), Some(&(2..=2, 4)));
+        assert_eq!(runs.last(), Some(&(9..=9, 1)));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = TestRng::new(12345);
+
+        let assignments: Vec<_> = (0..2_000)
+            .map(|line| {
+                let start = rng.below(1_000) as u64;
+                let end = start + rng.below(50) as u64;
+                (id(line, 0), start..=end)
+            })
+            .collect();
+        let index = CoverageIndex::new(assignments.clone());
+
+        for _ in 0..200 {
+            let start = rng.below(1_100) as u64;
+            let end = start + rng.below(20) as u64;
+            let mut expected: Vec<_> = assignments
+                .iter()
+                .filter(|(_, range)| *range.start() <= end && start <= *range.end())
+                .map(|(id, _)| *id)
+                .collect();
+            expected.sort_unstable();
+
+            assert_eq!(index.overlapping(start..=end), expected);
+            assert_eq!(
+                index.coverage(start),
+                assignments
+                    .iter()
+                    .filter(|(_, range)| range.contains(&start))
+                    .count()
+            );
+        }
+    }
+
+    #[test]
+    fn ends_at_max() {
+        let index = CoverageIndex::new([(id(1, 0), 5..=u64::MAX)]);
+
+        assert_eq!(index.coverage(u64::MAX), 1);
+        assert_eq!(index.covering(u64::MAX), [id(1, 0)]);
+
+        let index = CoverageIndex::new([(id(1, 0), 5..=u64::MAX), (id(1, 1), 3..=7)]);
+        let runs: Vec<_> = index.coverage_runs().collect();
+        assert_eq!(runs, [(3..=4, 1), (5..=7, 2), (8..=u64::MAX, 1)]);
+    }
+}
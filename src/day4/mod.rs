@@ -1,48 +1,59 @@
+use std::fmt;
 use std::ops::RangeInclusive;
 
+use crate::interval_set::IntervalSet;
+
+pub mod groups;
+pub mod index;
+
 pub fn part1(input: &str) -> u64 {
     input
         .lines()
         .filter(|line| {
-            let (first_elf_range, second_elf_range) = elf_ranges(line);
-            is_superset(&first_elf_range, &second_elf_range)
-                || is_superset(&second_elf_range, &first_elf_range)
+            let (first_elf, second_elf) = elf_sections(line);
+            first_elf.is_superset(&second_elf) || second_elf.is_superset(&first_elf)
         })
         .count() as u64
 }
 
 fn elf_ranges(line: &str) -> (RangeInclusive<u64>, RangeInclusive<u64>) {
-    let bytes = line.as_bytes();
-    let mut start = 0;
-    let mut end = 0;
-    while bytes[end] != b'-' {
-        end += 1;
+    let assignments = parse_assignments(line).unwrap_or_else(|err| panic!("{err}"));
+    match <[_; 2]>::try_from(assignments) {
+        Ok([first, second]) => (first, second),
+        Err(assignments) => panic!("Expected two elves but found {}", assignments.len()),
     }
-    let first_start = line[start..end].parse().expect("First elf invalid start!");
+}
 
-    start = end + 1;
-    end = start;
-    while bytes[end] != b',' {
-        end += 1;
-    }
-    let first_end = line[start..end].parse().expect("First elf invalid end!");
+/// Parses any number of comma separated `start-end` section assignments.
+pub fn parse_assignments(line: &str) -> Result<Vec<RangeInclusive<u64>>, InvalidAssignment> {
+    line.split(',')
+        .map(|assignment| {
+            let invalid = || InvalidAssignment(assignment.to_string());
+            let (start, end) = assignment.trim().split_once('-').ok_or_else(invalid)?;
+            let start: u64 = start.parse().map_err(|_| invalid())?;
+            let end: u64 = end.parse().map_err(|_| invalid())?;
+            if start > end {
+                return Err(invalid());
+            }
+            Ok(start..=end)
+        })
+        .collect()
+}
 
-    start = end + 1;
-    end = start;
-    while bytes[end] != b'-' {
-        end += 1;
-    }
-    let second_start = line[start..end].parse().expect("Second elf invalid start!");
-    let second_end = line[end + 1..].parse().expect("Second elf invalid end!");
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidAssignment(pub String);
 
-    // This is actually wasted effort. I was hoping there was some `range.contains(&range)` logic
-    // but there doesn't appear to be. I'll leave this in here though because it's nice to use
-    // random parts of Rust sometimes. Grease those wheels. Don't get ... rusty ...
-    (first_start..=first_end, second_start..=second_end)
+impl fmt::Display for InvalidAssignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid section assignment {:?}", self.0)
+    }
 }
 
-fn is_superset(r1: &RangeInclusive<u64>, r2: &RangeInclusive<u64>) -> bool {
-    r1.start() <= r2.start() && r1.end() >= r2.end()
+impl std::error::Error for InvalidAssignment {}
+
+fn elf_sections(line: &str) -> (IntervalSet<u64>, IntervalSet<u64>) {
+    let (first_elf_range, second_elf_range) = elf_ranges(line);
+    (first_elf_range.into(), second_elf_range.into())
 }
 
 pub fn part2(input: &str) -> u64 {
@@ -51,17 +62,12 @@ pub fn part2(input: &str) -> u64 {
         .filter(|line| {
             // TODO could optimize - if first_elf_start > second_elf_end then no need to parse the
             // other values.
-            let (first_elf_range, second_elf_range) = elf_ranges(line);
-            intersect(&first_elf_range, &second_elf_range)
+            let (first_elf, second_elf) = elf_sections(line);
+            !first_elf.is_disjoint(&second_elf)
         })
         .count() as u64
 }
 
-fn intersect(r1: &RangeInclusive<u64>, r2: &RangeInclusive<u64>) -> bool {
-    let disjoint = r1.end() < r2.start() || r1.start() > r2.end();
-    !disjoint
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +81,19 @@ mod tests {
 
     const INPUT: &str = include_str!("./input.txt");
 
+    #[test]
+    fn any_number_of_assignments() {
+        assert_eq!(parse_assignments("5-7"), Ok(vec![5..=7]));
+        assert_eq!(
+            parse_assignments("1-2,3-4,5-9"),
+            Ok(vec![1..=2, 3..=4, 5..=9])
+        );
+        assert_eq!(
+            parse_assignments("1-2,x-4"),
+            Err(InvalidAssignment("x-4".to_string()))
+        );
+    }
+
     mod part1 {
         use super::*;
 
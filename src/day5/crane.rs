@@ -0,0 +1,144 @@
+/// Decides what order crates land in when they're moved between stacks.
+pub trait Crane {
+    /// Rearranges `crates`, lifted off the top of a stack bottom crate first, into the order they
+    /// land on the new stack (also bottom crate first).
+    fn arrange<T>(&self, crates: &mut [T]);
+
+    /// `Some(true)` if every lift lands in reverse and `Some(false)` if every lift lands as it
+    /// was, so crates can be moved without looking at them. `None` means `arrange` has to be asked.
+    fn lands_reversed(&self) -> Option<bool> {
+        None
+    }
+}
+
+/// Moves crates one at a time so they land in reverse order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn arrange<T>(&self, crates: &mut [T]) {
+        crates.reverse();
+    }
+
+    fn lands_reversed(&self) -> Option<bool> {
+        Some(true)
+    }
+}
+
+/// Moves all the crates at once so they keep their order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn arrange<T>(&self, _crates: &mut [T]) {}
+
+    fn lands_reversed(&self) -> Option<bool> {
+        Some(false)
+    }
+}
+
+/// Can only lift `capacity` crates at a time so big moves are split into batches, top crates
+/// first. Each batch keeps its order.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchCrane {
+    capacity: usize,
+}
+
+impl BatchCrane {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Crane has to lift something");
+        Self { capacity }
+    }
+}
+
+impl Crane for BatchCrane {
+    fn arrange<T>(&self, crates: &mut [T]) {
+        // Reversing everything puts the batches in the order they're moved, then reversing each
+        // batch again puts its crates back in their original order.
+        //
+        //     [1, 2, 3, 4, 5] => [5, 4, 3, 2, 1] => [4, 5, 2, 3, 1]
+        //
+        crates.reverse();
+        crates
+            .chunks_mut(self.capacity)
+            .for_each(|batch| batch.reverse());
+    }
+}
+
+/// Like `BatchCrane` but every other batch (the second, fourth, ...) gets flipped on the way.
+#[derive(Clone, Copy, Debug)]
+pub struct AlternatingCrane {
+    capacity: usize,
+}
+
+impl AlternatingCrane {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Crane has to lift something");
+        Self { capacity }
+    }
+}
+
+impl Crane for AlternatingCrane {
+    fn arrange<T>(&self, crates: &mut [T]) {
+        // Same as `BatchCrane` except the flipped batches are left reversed.
+        crates.reverse();
+        crates
+            .chunks_mut(self.capacity)
+            .step_by(2)
+            .for_each(|batch| batch.reverse());
+    }
+}
+
+impl<C: Crane + ?Sized> Crane for &C {
+    fn arrange<T>(&self, crates: &mut [T]) {
+        (**self).arrange(crates);
+    }
+
+    fn lands_reversed(&self) -> Option<bool> {
+        (**self).lands_reversed()
+    }
+}
+
+impl<C: Crane + ?Sized> Crane for Box<C> {
+    fn arrange<T>(&self, crates: &mut [T]) {
+        (**self).arrange(crates);
+    }
+
+    fn lands_reversed(&self) -> Option<bool> {
+        (**self).lands_reversed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arranged(crane: impl Crane) -> String {
+        let mut crates: Vec<String> = "12345".chars().map(String::from).collect();
+        crane.arrange(&mut crates);
+        crates.concat()
+    }
+
+    #[test]
+    fn crate_movers() {
+        assert_eq!(arranged(CrateMover9000), "54321");
+        assert_eq!(arranged(CrateMover9001), "12345");
+    }
+
+    #[test]
+    fn batch_crane() {
+        assert_eq!(arranged(BatchCrane::new(2)), "45231");
+        assert_eq!(arranged(BatchCrane::new(3)), "34512");
+
+        // The extremes are the two puzzle cranes.
+        assert_eq!(arranged(BatchCrane::new(1)), "54321");
+        assert_eq!(arranged(BatchCrane::new(5)), "12345");
+    }
+
+    #[test]
+    fn alternating_crane() {
+        assert_eq!(arranged(AlternatingCrane::new(2)), "45321");
+        assert_eq!(arranged(AlternatingCrane::new(1)), "54321");
+        assert_eq!(arranged(AlternatingCrane::new(10)), "12345");
+    }
+}
@@ -0,0 +1,395 @@
+//! The ASCII drawing of the stacks at the top of the puzzle input.
+//!
+//! ```text
+//!     [D]
+//! [N] [C]
+//! [Z] [M] [P]
+//!  1   2   3
+//! ```
+//!
+//! Crate labels can be more than one character (`[AB]`) and there can be any number of stacks.
+//! Which stack a crate is in comes from the numbered footer rather than a fixed column width:
+//! every crate belongs to the stack number closest to the middle of it.
+
+use std::fmt;
+
+/// Reads a drawing into stacks, bottom crate first.
+pub fn parse(drawing: &str) -> Result<Vec<Vec<String>>, DrawingError> {
+    parse_lines(drawing.lines(), str::to_string)
+}
+
+/// Like `parse` but stops at the first empty line, so it can read the top of a full input.
+/// Each label is passed to `label` to make the crate that goes on the stack.
+pub(super) fn parse_lines<'a, T>(
+    lines: impl Iterator<Item = &'a str>,
+    mut label: impl FnMut(&'a str) -> T,
+) -> Result<Vec<Vec<T>>, DrawingError> {
+    let lines: Vec<&str> = lines.take_while(|line| !line.is_empty()).collect();
+    let Some((footer, rows)) = lines.split_last() else {
+        return Err(DrawingError {
+            line: 1,
+            kind: DrawingErrorKind::MissingFooter,
+        });
+    };
+
+    let columns = footer_columns(footer).map_err(|kind| DrawingError {
+        line: lines.len(),
+        kind,
+    })?;
+
+    let mut stacks: Vec<Vec<T>> = columns.iter().map(|_| Vec::new()).collect();
+    for (idx, row) in rows.iter().enumerate() {
+        let error = |kind| DrawingError {
+            line: idx + 1,
+            kind,
+        };
+
+        let mut filled = vec![false; columns.len()];
+        for (middle, name) in crates(row).map_err(error)? {
+            let stack = nearest(&columns, middle);
+            if filled[stack] {
+                return Err(error(DrawingErrorKind::SharedColumn {
+                    // `middle` is doubled and 0 indexed.
+                    column: middle / 2 + 1,
+                    stack: stack + 1,
+                }));
+            }
+            filled[stack] = true;
+            stacks[stack].push(label(name));
+        }
+    }
+
+    stacks.iter_mut().for_each(|stack| stack.reverse());
+    Ok(stacks)
+}
+
+// Positions are measured in characters and every "middle" is doubled (start + end) so labels
+// with an even width don't need fractions.
+
+/// The middle of each stack number in the footer, checking they go 1, 2, 3, ...
+fn footer_columns(footer: &str) -> Result<Vec<usize>, DrawingErrorKind> {
+    let mut columns = Vec::new();
+    let mut chars = footer.chars().enumerate().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if c == ' ' {
+            continue;
+        }
+
+        let mut number = String::from(c);
+        let mut end = start;
+        while let Some((idx, c)) = chars.next_if(|(_, c)| *c != ' ') {
+            number.push(c);
+            end = idx;
+        }
+
+        if number.starts_with('[') {
+            return Err(DrawingErrorKind::MissingFooter);
+        }
+        let expected = columns.len() + 1;
+        if number != expected.to_string() {
+            return Err(DrawingErrorKind::UnexpectedStackNumber {
+                expected,
+                found: number,
+            });
+        }
+        columns.push(start + end);
+    }
+
+    if columns.is_empty() {
+        return Err(DrawingErrorKind::MissingFooter);
+    }
+    Ok(columns)
+}
+
+/// The middle and label of every crate in `row`, left to right.
+fn crates(row: &str) -> Result<Vec<(usize, &str)>, DrawingErrorKind> {
+    let mut crates = Vec::new();
+    // Character positions for the columns and byte offsets for slicing out the labels.
+    let mut chars = row.char_indices().enumerate();
+
+    while let Some((start, (open, c))) = chars.next() {
+        match c {
+            ' ' => {}
+            '[' => {
+                let invalid = DrawingErrorKind::InvalidCrate { column: start + 1 };
+                let (end, close) = loop {
+                    match chars.next() {
+                        Some((end, (close, ']'))) => break (end, close),
+                        Some((_, (_, c))) if c != ' ' && c != '[' => {}
+                        _ => return Err(invalid),
+                    }
+                };
+                // `[` is one byte.
+                let label = &row[open + 1..close];
+                if label.is_empty() {
+                    return Err(invalid);
+                }
+                crates.push((start + end, label));
+            }
+            found => {
+                return Err(DrawingErrorKind::UnexpectedCharacter {
+                    column: start + 1,
+                    found,
+                })
+            }
+        }
+    }
+
+    Ok(crates)
+}
+
+/// The index of the column closest to `middle`, preferring the left one on a tie.
+fn nearest(columns: &[usize], middle: usize) -> usize {
+    let idx = columns.partition_point(|column| *column < middle);
+    match (idx.checked_sub(1), columns.get(idx)) {
+        (Some(left), Some(right)) if middle - columns[left] > right - middle => idx,
+        (Some(left), _) => left,
+        (None, _) => idx,
+    }
+}
+
+/// Draws `stacks` (bottom crate first) the same way the puzzle does, numbered footer included.
+///
+/// Every stack is as wide as its widest crate (or stack number) and everything is centred in its
+/// column, which keeps the three character columns of the puzzle for single character labels.
+/// Every line is padded to the full width like the puzzle input, so `parse` followed by `render`
+/// gives back the original drawing apart from any trailing whitespace that was trimmed from it.
+pub fn render(stacks: &[Vec<String>]) -> String {
+    let numbers: Vec<String> = (1..=stacks.len()).map(|n| n.to_string()).collect();
+    let widths: Vec<usize> = stacks
+        .iter()
+        .zip(&numbers)
+        .map(|(stack, number)| {
+            let widest = stack.iter().map(|label| label.chars().count() + 2);
+            widest.chain([number.len(), 3]).max().unwrap()
+        })
+        .collect();
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut drawing = String::new();
+    for level in (0..height).rev() {
+        let cells: Vec<String> = stacks
+            .iter()
+            .zip(&widths)
+            .map(|(stack, &width)| match stack.get(level) {
+                Some(label) => format!("{:^width$}", format!("[{label}]")),
+                None => " ".repeat(width),
+            })
+            .collect();
+        drawing.push_str(&cells.join(" "));
+        drawing.push('\n');
+    }
+
+    let footer: Vec<String> = numbers
+        .iter()
+        .zip(&widths)
+        .map(|(number, &width)| format!("{number:^width$}"))
+        .collect();
+    drawing.push_str(&footer.join(" "));
+    drawing.push('\n');
+
+    drawing
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrawingError {
+    /// 1 indexed line of the drawing.
+    pub line: usize,
+    pub kind: DrawingErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DrawingErrorKind {
+    /// The last line doesn't number the stacks.
+    MissingFooter,
+    UnexpectedStackNumber {
+        expected: usize,
+        found: String,
+    },
+    /// A `[` that isn't closed or has nothing in it. Columns are 1 indexed characters.
+    InvalidCrate {
+        column: usize,
+    },
+    UnexpectedCharacter {
+        column: usize,
+        found: char,
+    },
+    /// Two crates on the same line are closest to the same stack number.
+    SharedColumn {
+        column: usize,
+        stack: usize,
+    },
+}
+
+impl fmt::Display for DrawingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            DrawingErrorKind::MissingFooter => write!(f, "expected the stack numbers"),
+            DrawingErrorKind::UnexpectedStackNumber { expected, found } => {
+                write!(f, "expected stack {expected} but found {found:?}")
+            }
+            DrawingErrorKind::InvalidCrate { column } => {
+                write!(
+                    f,
+                    "the crate at column {column} has no label or isn't closed"
+                )
+            }
+            DrawingErrorKind::UnexpectedCharacter { column, found } => {
+                write!(f, "unexpected {found:?} at column {column}")
+            }
+            DrawingErrorKind::SharedColumn { column, stack } => write!(
+                f,
+                "the crate at column {column} lines up with stack {stack} like another crate"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DrawingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    fn stack(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    fn trim_lines(drawing: &str) -> Vec<&str> {
+        drawing.lines().map(str::trim_end).collect()
+    }
+
+    const EXAMPLE: &str = "    [D]    \n\
+                           [N] [C]    \n\
+                           [Z] [M] [P]\n \
+                           1   2   3 \n";
+
+    #[test]
+    fn example() {
+        let stacks = parse(EXAMPLE).unwrap();
+        assert_eq!(
+            stacks,
+            [stack(&["Z", "N"]), stack(&["M", "C", "D"]), stack(&["P"])]
+        );
+
+        assert_eq!(render(&stacks), EXAMPLE);
+    }
+
+    #[test]
+    fn round_trip_ignores_trailing_whitespace() {
+        let trimmed = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n";
+
+        assert_eq!(
+            trim_lines(&render(&parse(trimmed).unwrap())),
+            trim_lines(trimmed)
+        );
+    }
+
+    #[test]
+    fn empty_stacks() {
+        let stacks = vec![stack(&["A"]), Vec::new(), stack(&["B", "C"]), Vec::new()];
+        let drawing = render(&stacks);
+
+        assert_eq!(
+            drawing,
+            "        [C]    \n\
+             [A]     [B]    \n \
+             1   2   3   4 \n"
+        );
+        assert_eq!(parse(&drawing).unwrap(), stacks);
+
+        assert_eq!(render(&[Vec::new(), Vec::new()]), " 1   2 \n");
+    }
+
+    #[test]
+    fn multi_character_labels() {
+        let drawing = "     [CD]\n\
+                       [AB] [E]  [F]\n \
+                       1    2    3\n";
+
+        assert_eq!(
+            parse(drawing).unwrap(),
+            [stack(&["AB"]), stack(&["E", "CD"]), stack(&["F"])]
+        );
+    }
+
+    #[test]
+    fn more_than_nine_stacks() {
+        let mut drawing = "[A] ".repeat(11);
+        drawing.push_str("[B]\n");
+        drawing.push_str(" 1   2   3   4   5   6   7   8   9  10  11  12\n");
+
+        let stacks = parse(&drawing).unwrap();
+        assert_eq!(stacks.len(), 12);
+        assert_eq!(stacks[11], stack(&["B"]));
+    }
+
+    #[test]
+    fn render_round_trips() {
+        let mut rng = TestRng::new(5);
+
+        for _ in 0..50 {
+            let stacks: Vec<Vec<String>> = (0..1 + rng.below(15))
+                .map(|_| {
+                    (0..rng.below(5))
+                        .map(|_| {
+                            (0..1 + rng.below(4))
+                                .map(|_| (b'A' + rng.below(26) as u8) as char)
+                                .collect()
+                        })
+                        .collect()
+                })
+                .collect();
+
+            assert_eq!(parse(&render(&stacks)).unwrap(), stacks);
+        }
+    }
+
+    #[test]
+    fn invalid_drawings() {
+        let error = |drawing: &str| parse(drawing).unwrap_err();
+
+        assert_eq!(
+            error("[A] [B]\n"),
+            DrawingError {
+                line: 1,
+                kind: DrawingErrorKind::MissingFooter,
+            }
+        );
+        assert_eq!(
+            error("[A] [B]\n 1   3\n"),
+            DrawingError {
+                line: 2,
+                kind: DrawingErrorKind::UnexpectedStackNumber {
+                    expected: 2,
+                    found: "3".into(),
+                },
+            }
+        );
+        assert_eq!(
+            error("[A] [B\n 1   2\n").kind,
+            DrawingErrorKind::InvalidCrate { column: 5 }
+        );
+        assert_eq!(
+            error("[A] []\n 1   2\n").kind,
+            DrawingErrorKind::InvalidCrate { column: 5 }
+        );
+        assert_eq!(
+            error("[A] B\n 1   2\n").kind,
+            DrawingErrorKind::UnexpectedCharacter {
+                column: 5,
+                found: 'B',
+            }
+        );
+        assert_eq!(
+            error("[A] [B]\n 1\n").kind,
+            DrawingErrorKind::SharedColumn {
+                column: 6,
+                stack: 1,
+            }
+        );
+    }
+}
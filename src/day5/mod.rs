@@ -1,165 +1,274 @@
+use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 
+use crane::{Crane, CrateMover9000, CrateMover9001};
+use drawing::DrawingError;
+
+pub mod crane;
+pub mod drawing;
+pub mod planner;
+pub mod segments;
+pub mod yard;
+
 pub fn part1(input: &str) -> String {
-    let mut stacks = Vec::new();
+    rearrange(input, CrateMover9000)
+        .unwrap_or_else(|err| panic!("Invalid procedure: {err}"))
+        .concat()
+}
 
-    let mut lines = input.lines();
-    for line in lines.by_ref().take_while(|line| !line.is_empty()) {
-        parse_inverted_stacks(&mut stacks, line);
-    }
+pub fn part2(input: &str) -> String {
+    rearrange(input, CrateMover9001)
+        .unwrap_or_else(|err| panic!("Invalid procedure: {err}"))
+        .concat()
+}
 
-    // Our stacks are all upside-down. I think reversing them might be clearer than `VecDeque`.
-    stacks.iter_mut().for_each(|stack| stack.reverse());
+/// Runs the whole procedure in `input` with `crane` and returns the label of the crate on top of
+/// each stack, skipping empty stacks.
+pub fn rearrange<C: Crane>(input: &str, crane: C) -> Result<Vec<String>, RearrangeError> {
+    let mut labels = LabelTable::default();
+    let (mut stacks, instructions) = parse_input_with(input, |label| labels.intern(label))?;
 
-    for instruction in lines {
-        let Instruction {
-            num_crates,
-            start_stack,
-            end_stack,
-        } = instruction.parse().unwrap();
+    for (step, instruction) in instructions.iter().enumerate() {
+        let mut crates = lift(&mut stacks, instruction).map_err(|kind| MoveError { step, kind })?;
+        crane.arrange(&mut crates);
+        stacks[instruction.end_stack - 1].extend(crates);
+    }
 
-        // The instructions are 1 indexed.
-        let start_stack = start_stack - 1;
-        let end_stack = end_stack - 1;
+    Ok(labels.labels(tops(&stacks)))
+}
 
-        for _ in 0..num_crates {
-            let c = stacks[start_stack]
-                .pop()
-                .expect("Asked to remove from empty stack");
-            stacks[end_stack].push(c);
+/// Checks `instruction` can be done and takes the crates it moves off the top of the starting
+/// stack, bottom crate first.
+fn lift<T>(stacks: &mut [Vec<T>], instruction: &Instruction) -> Result<Vec<T>, MoveErrorKind> {
+    let Instruction {
+        num_crates,
+        start_stack,
+        end_stack,
+    } = *instruction;
+    for stack in [start_stack, end_stack] {
+        if stack == 0 || stack > stacks.len() {
+            return Err(MoveErrorKind::NoSuchStack(stack));
         }
     }
 
-    let bytes: Vec<u8> = stacks
+    // The instructions are 1 indexed.
+    let start = &mut stacks[start_stack - 1];
+    let split = start
+        .len()
+        .checked_sub(num_crates)
+        .ok_or(MoveErrorKind::NotEnoughCrates {
+            stack: start_stack,
+            needed: num_crates,
+            available: start.len(),
+        })?;
+
+    Ok(start.split_off(split))
+}
+
+fn tops<T: Clone>(stacks: &[Vec<T>]) -> Vec<T> {
+    stacks
         .iter()
-        .filter_map(|stack| stack.last().copied())
-        .collect();
-    String::from_utf8(bytes).expect("Each crate is a valid ASCII letter")
+        .filter_map(|stack| stack.last().cloned())
+        .collect()
 }
 
-fn parse_inverted_stacks(stacks: &mut Vec<Vec<u8>>, line: &str) {
-    // Each box is a '[', a letter, and a ']' separated by spaces. The last stack doesn't have
-    // a trailing space so we add 1 before dividing. This should only resize once.
-    let num_entries = (line.len() + 1) / 4;
-    stacks.resize_with(num_entries, Vec::new);
+/// Numbers every different crate label so the stacks can hold a `usize` per crate rather than a
+/// `String`. The labels are borrowed from the input.
+#[derive(Debug, Default)]
+struct LabelTable<'a> {
+    labels: Vec<&'a str>,
+    ids: HashMap<&'a str, usize>,
+}
+
+impl<'a> LabelTable<'a> {
+    fn intern(&mut self, label: &'a str) -> usize {
+        *self.ids.entry(label).or_insert_with(|| {
+            self.labels.push(label);
+            self.labels.len() - 1
+        })
+    }
+
+    fn labels(&self, ids: impl IntoIterator<Item = usize>) -> Vec<String> {
+        ids.into_iter()
+            .map(|id| self.labels[id].to_string())
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveError {
+    /// 0 indexed position of the instruction in the procedure.
+    pub step: usize,
+    pub kind: MoveErrorKind,
+}
 
-    for (chunk, stack) in line.as_bytes().chunks(4).zip(stacks.iter_mut()) {
-        if let [b'[', letter, ..] = chunk {
-            stack.push(*letter);
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveErrorKind {
+    /// 1 indexed, like the instructions.
+    NoSuchStack(usize),
+    NotEnoughCrates {
+        stack: usize,
+        needed: usize,
+        available: usize,
+    },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "step {}: ", self.step)?;
+        match self.kind {
+            MoveErrorKind::NoSuchStack(stack) => write!(f, "there is no stack {stack}"),
+            MoveErrorKind::NotEnoughCrates {
+                stack,
+                needed,
+                available,
+            } => write!(
+                f,
+                "asked to move {needed} crates from stack {stack} which only has {available}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Why a whole input couldn't be rearranged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RearrangeError {
+    Drawing(DrawingError),
+    Instruction {
+        /// 1 indexed line of the input.
+        line: usize,
+        source: InvalidInstruction,
+    },
+    Move(MoveError),
+}
+
+impl From<DrawingError> for RearrangeError {
+    fn from(err: DrawingError) -> Self {
+        Self::Drawing(err)
+    }
+}
+
+impl From<MoveError> for RearrangeError {
+    fn from(err: MoveError) -> Self {
+        Self::Move(err)
+    }
+}
+
+impl fmt::Display for RearrangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Drawing(err) => write!(f, "invalid drawing: {err}"),
+            Self::Instruction { line, source } => write!(f, "line {line}: {source}"),
+            Self::Move(err) => err.fmt(f),
         }
     }
 }
 
-struct Instruction {
-    num_crates: usize,
+impl std::error::Error for RearrangeError {}
+
+/// `move {num_crates} from {start_stack} to {end_stack}`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub num_crates: usize,
 
     // 1 indexed
-    start_stack: usize,
+    pub start_stack: usize,
 
     // 1 indexed
-    end_stack: usize,
+    pub end_stack: usize,
 }
 
 impl FromStr for Instruction {
-    type Err = std::convert::Infallible; // _still_ lazy
+    type Err = InvalidInstruction;
 
     fn from_str(instruction: &str) -> Result<Self, Self::Err> {
-        let mut instructions = instruction.split(' ');
-
-        let _move = instructions.next();
-        let num_crates = instructions.next().expect("Should have number of crates");
-        let _from = instructions.next();
-        let start_stack = instructions.next().expect("Should have starting stack");
-        let _to = instructions.next();
-        let end_stack = instructions.next().expect("Should have ending stack");
-
-        let num_crates = num_crates
-            .parse::<usize>()
-            .expect("Number of crates should be a number");
-
-        let start_stack = start_stack
-            .parse::<usize>()
-            .expect("Starting stack should be a number");
-        let end_stack = end_stack
-            .parse::<usize>()
-            .expect("Ending stack should be a number");
+        let invalid = || InvalidInstruction(instruction.to_string());
+        let words: Vec<&str> = instruction.split(' ').collect();
+        let ["move", num_crates, "from", start_stack, "to", end_stack] = words[..] else {
+            return Err(invalid());
+        };
+        let number = |word: &str| word.parse::<usize>().map_err(|_| invalid());
 
         Ok(Self {
-            num_crates,
-            start_stack,
-            end_stack,
+            num_crates: number(num_crates)?,
+            start_stack: number(start_stack)?,
+            end_stack: number(end_stack)?,
         })
     }
 }
 
-pub fn part2(input: &str) -> String {
-    let mut stacks = Vec::new();
-
-    let mut lines = input.lines();
-    for line in lines.by_ref().take_while(|line| !line.is_empty()) {
-        parse_inverted_stacks(&mut stacks, line);
-    }
-
-    // Our stacks are all upside-down. I think reversing them might be clearer than `VecDeque`.
-    stacks.iter_mut().for_each(|stack| stack.reverse());
-
-    for instruction in lines {
-        let Instruction {
-            num_crates,
-            start_stack,
-            end_stack,
-        } = instruction.parse().unwrap();
-
-        // The instructions are 1 indexed.
-        let start_stack = start_stack - 1;
-        let end_stack = end_stack - 1;
-
-        //  Move 1 from 3 to 5 (1 indexed)
-        //  Move 1 from 2 to 4 (0 indexed)
-        //      split at 2 + 1 = 3
-        //      (start)    (end)
-        //      [0, 1, 2], [3, 4, 5, 6, 7, 8]
-        //      start[start_index] = start[2], end[end_index - start_index - 1] = end[1]
-        //
-        //  Move 1 from 5 to 3 (1 indexed)
-        //  Move 1 from 4 to 2 (0 indexed)
-        //      split at 2 + 1 = 3
-        //      (end)      (start)
-        //      [0, 1, 2], [3, 4, 5, 6, 7, 8]
-        //      (start)             (end)
-        //      [3, 4, 5, 6, 7, 8], [0, 1, 2]
-        //      start[start_index - end_index - 1] = start[1], end[end_index] = end[2]
-        //
-
-        // Waiting on https://doc.rust-lang.org/nightly/std/primitive.slice.html#method.get_many_mut
-        let (start, end) = if start_stack < end_stack {
-            let (contains_start, contains_end) = stacks.split_at_mut(start_stack + 1);
-            (
-                &mut contains_start[start_stack],
-                &mut contains_end[end_stack - start_stack - 1],
-            )
-        } else {
-            let (contains_end, contains_start) = stacks.split_at_mut(end_stack + 1);
-            (
-                &mut contains_start[start_stack - end_stack - 1],
-                &mut contains_end[end_stack],
-            )
-        };
-        end.extend(start.drain(start.len() - num_crates..));
+/// A line that isn't `move {num_crates} from {start_stack} to {end_stack}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidInstruction(pub String);
+
+impl fmt::Display for InvalidInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a line like \"move 1 from 2 to 3\" but found {:?}",
+            self.0
+        )
     }
+}
 
-    let bytes: Vec<u8> = stacks
-        .iter()
-        .filter_map(|stack| stack.last().copied())
-        .collect();
-    String::from_utf8(bytes).expect("Each crate is a valid ASCII letter")
+impl std::error::Error for InvalidInstruction {}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.num_crates, self.start_stack, self.end_stack
+        )
+    }
+}
+
+/// Splits the input into the starting stacks (bottom crate first) and the instructions.
+fn parse_input(input: &str) -> Result<(Vec<Vec<String>>, Vec<Instruction>), RearrangeError> {
+    parse_input_with(input, str::to_string)
+}
+
+/// Like `parse_input` but each crate is made from its label by `label`.
+fn parse_input_with<'a, T>(
+    input: &'a str,
+    label: impl FnMut(&'a str) -> T,
+) -> Result<(Vec<Vec<T>>, Vec<Instruction>), RearrangeError> {
+    let mut lines = input.lines().enumerate();
+    let stacks = drawing::parse_lines(lines.by_ref().map(|(_, line)| line), label)?;
+
+    let instructions = lines
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(idx, line)| {
+            line.parse().map_err(|source| RearrangeError::Instruction {
+                line: idx + 1,
+                source,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((stacks, instructions))
+}
+
+/// The opposite of `parse_input`: draws `stacks` (bottom crate first) and lists the instructions
+/// after them.
+pub fn write_input(stacks: &[Vec<String>], instructions: &[Instruction]) -> String {
+    let mut input = drawing::render(stacks);
+    input.push('\n');
+    for instruction in instructions {
+        input.push_str(&instruction.to_string());
+        input.push('\n');
+    }
+    input
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example_file() -> String {
+    pub(super) fn example_file() -> String {
         // Note whitespace is important here!
         let mut file = "    [D]    \n".to_string();
         file.push_str("[N] [C]    \n");
@@ -203,4 +312,112 @@ mod tests {
             assert_eq!(part2(INPUT), "HZFZCCWWV");
         }
     }
+
+    mod other_cranes {
+        use super::*;
+        use crate::day5::crane::{AlternatingCrane, BatchCrane};
+
+        #[test]
+        fn batch_crane() {
+            assert_eq!(
+                rearrange(&example_file(), BatchCrane::new(1)).map(|tops| tops.concat()),
+                Ok("CMZ".into())
+            );
+            assert_eq!(
+                rearrange(&example_file(), BatchCrane::new(3)).map(|tops| tops.concat()),
+                Ok("MCD".into())
+            );
+
+            // Only the second move has more crates than the crane can lift. It moves ND first
+            // and then Z, leaving Z on top.
+            assert_eq!(
+                rearrange(&example_file(), BatchCrane::new(2)).map(|tops| tops.concat()),
+                Ok("MCZ".into())
+            );
+        }
+
+        #[test]
+        fn alternating_crane() {
+            assert_eq!(
+                rearrange(&example_file(), AlternatingCrane::new(1)).map(|tops| tops.concat()),
+                Ok("CMZ".into())
+            );
+        }
+
+        #[test]
+        fn invalid_procedure() {
+            let mut file = example_file();
+            file.push_str("move 5 from 1 to 2\n");
+
+            assert_eq!(
+                rearrange(&file, CrateMover9000),
+                Err(RearrangeError::Move(MoveError {
+                    step: 4,
+                    kind: MoveErrorKind::NotEnoughCrates {
+                        stack: 1,
+                        needed: 5,
+                        available: 1,
+                    },
+                }))
+            );
+        }
+
+        #[test]
+        fn invalid_input() {
+            let mut file = example_file();
+            file.push_str("move 1 from two to 3\n");
+            assert_eq!(
+                rearrange(&file, CrateMover9000),
+                Err(RearrangeError::Instruction {
+                    line: 10,
+                    source: InvalidInstruction("move 1 from two to 3".into()),
+                })
+            );
+
+            let file = example_file().replace(" 1   2   3 ", "");
+            assert!(matches!(
+                rearrange(&file, CrateMover9000),
+                Err(RearrangeError::Drawing(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn write_input_round_trips() {
+        let (stacks, instructions) = parse_input(&example_file()).unwrap();
+
+        assert_eq!(write_input(&stacks, &instructions), example_file());
+    }
+
+    #[test]
+    fn wide_drawing() {
+        let mut file = format!("[AB]{:45}[K]\n", "");
+        file.push_str("[CD] [E]  [F]  [G]  [H]  [I]  [J]  [X]  [Y]  [Z] [LM]\n");
+        file.push_str(" 1    2    3    4    5    6    7    8    9   10   11\n");
+        file.push('\n');
+        file.push_str("move 2 from 1 to 11\n");
+        file.push_str("move 1 from 10 to 2\n");
+
+        assert_eq!(
+            rearrange(&file, CrateMover9001),
+            Ok(["Z", "F", "G", "H", "I", "J", "X", "Y", "AB"]
+                .map(String::from)
+                .to_vec())
+        );
+    }
+
+    #[test]
+    fn repeated_labels() {
+        let file = "[A]     \n\
+                    [A] [BB]\n \
+                     1   2  \n\
+                    \n\
+                    move 1 from 2 to 1\n\
+                    move 3 from 1 to 2\n\
+                    move 1 from 2 to 1\n";
+
+        let expected = Ok(vec!["A".to_string(), "A".to_string()]);
+        assert_eq!(rearrange(file, CrateMover9000), expected);
+        assert_eq!(segments::rearrange(file, CrateMover9000), expected);
+    }
 }
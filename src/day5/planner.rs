@@ -0,0 +1,503 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::crane::Crane;
+use super::Instruction;
+
+/// Works out which instructions turn one arrangement of crates into another.
+///
+/// Small problems are solved exactly with a breadth first search over every arrangement a crane
+/// can reach, which gives a shortest plan. Once the search has seen `max_states` arrangements it
+/// gives up and, if there are at least three stacks, builds the target greedily instead:
+///
+/// 1. Every stack keeps the longest run of crates from its bottom that already match the target.
+///    Those crates are never moved again.
+/// 2. Each round picks the cheapest crate to add next to one of those runs, moves everything in
+///    the way onto other stacks one crate at a time and then moves the crate into place.
+/// 3. Runs of identical single crate moves are merged into one instruction when the crane would
+///    land the crates in the same order.
+///
+/// Each round costs at most one move more than the number of crates still out of place and puts
+/// at least one of them in place, so a greedy plan has at most `u * (u + 3) / 2` instructions
+/// where `u` is the number of crates that aren't already part of a matching run. There's no
+/// other guarantee about how close to the shortest plan it is.
+#[derive(Clone, Debug)]
+pub struct Planner<C> {
+    crane: C,
+    max_states: usize,
+}
+
+/// The instructions that reach the target, and whether there definitely isn't a shorter list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plan {
+    pub instructions: Vec<Instruction>,
+    pub shortest: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlanError {
+    DifferentStackCounts {
+        start: usize,
+        target: usize,
+    },
+    /// The target doesn't have exactly the same crates as the start.
+    DifferentCrates,
+    /// The search saw every arrangement the crane can reach and none of them were the target.
+    Unreachable,
+    /// The search gave up and there aren't enough stacks to plan greedily.
+    TooManyStates,
+}
+
+impl<C: Crane> Planner<C> {
+    pub fn new(crane: C) -> Self {
+        Self {
+            crane,
+            max_states: 100_000,
+        }
+    }
+
+    /// How many arrangements the exact search can look at before falling back to the greedy plan.
+    pub fn max_states(mut self, max_states: usize) -> Self {
+        self.max_states = max_states;
+        self
+    }
+
+    /// Plans how to get from `start` to `target`, both bottom crate first.
+    pub fn plan(&self, start: &[Vec<String>], target: &[Vec<String>]) -> Result<Plan, PlanError> {
+        if start.len() != target.len() {
+            return Err(PlanError::DifferentStackCounts {
+                start: start.len(),
+                target: target.len(),
+            });
+        }
+        let mut start_crates: Vec<&String> = start.iter().flatten().collect();
+        let mut target_crates: Vec<&String> = target.iter().flatten().collect();
+        start_crates.sort_unstable();
+        target_crates.sort_unstable();
+        if start_crates != target_crates {
+            return Err(PlanError::DifferentCrates);
+        }
+
+        match self.search(start, target) {
+            Search::Found(instructions) => Ok(Plan {
+                instructions,
+                shortest: true,
+            }),
+            Search::Exhausted => Err(PlanError::Unreachable),
+            Search::GaveUp if start.len() >= 3 => Ok(Plan {
+                instructions: self.merge_single_moves(start, greedy(start, target)),
+                shortest: false,
+            }),
+            Search::GaveUp => Err(PlanError::TooManyStates),
+        }
+    }
+
+    fn search(&self, start: &[Vec<String>], target: &[Vec<String>]) -> Search {
+        if start == target {
+            return Search::Found(Vec::new());
+        }
+
+        // Every arrangement seen so far in the order they were found, with the arrangement and
+        // instruction that led to it.
+        let mut states = vec![start.to_vec()];
+        let mut parents: Vec<Option<(usize, Instruction)>> = vec![None];
+        let mut seen = HashSet::from([start.to_vec()]);
+
+        let mut next = 0;
+        while next < states.len() {
+            let state = states[next].clone();
+            for instruction in moves(&state) {
+                let mut moved = state.clone();
+                let mut crates =
+                    super::lift(&mut moved, &instruction).expect("Only valid moves are generated");
+                self.crane.arrange(&mut crates);
+                moved[instruction.end_stack - 1].extend(crates);
+
+                if seen.contains(&moved) {
+                    continue;
+                }
+                if moved == target {
+                    let mut instructions = vec![instruction];
+                    let mut idx = next;
+                    while let Some((parent, instruction)) = parents[idx] {
+                        instructions.push(instruction);
+                        idx = parent;
+                    }
+                    instructions.reverse();
+                    return Search::Found(instructions);
+                }
+                if states.len() >= self.max_states {
+                    return Search::GaveUp;
+                }
+
+                seen.insert(moved.clone());
+                states.push(moved);
+                parents.push(Some((next, instruction)));
+            }
+            next += 1;
+        }
+
+        Search::Exhausted
+    }
+
+    /// Merges runs of the same single crate move into one instruction wherever the crane lands
+    /// the crates in the same order as moving them one at a time would.
+    fn merge_single_moves(
+        &self,
+        start: &[Vec<String>],
+        instructions: Vec<Instruction>,
+    ) -> Vec<Instruction> {
+        let mut stacks = start.to_vec();
+        let mut merged = Vec::new();
+
+        for run in instructions.chunk_by(|a, b| a == b) {
+            let instruction = Instruction {
+                num_crates: run.len(),
+                ..run[0]
+            };
+            let from = &stacks[instruction.start_stack - 1];
+            let lifted = &from[from.len() - run.len()..];
+
+            let mut arranged = lifted.to_vec();
+            self.crane.arrange(&mut arranged);
+            let one_at_a_time: Vec<String> = lifted.iter().rev().cloned().collect();
+
+            if arranged == one_at_a_time {
+                merged.push(instruction);
+            } else {
+                merged.extend_from_slice(run);
+            }
+
+            // Either way the crates end up in the same place.
+            let from = &mut stacks[instruction.start_stack - 1];
+            let moved = from.split_off(from.len() - run.len());
+            stacks[instruction.end_stack - 1].extend(moved.into_iter().rev());
+        }
+
+        merged
+    }
+}
+
+enum Search {
+    Found(Vec<Instruction>),
+    Exhausted,
+    GaveUp,
+}
+
+/// Every instruction that can be done from `stacks`.
+fn moves(stacks: &[Vec<String>]) -> impl Iterator<Item = Instruction> + '_ {
+    (0..stacks.len()).flat_map(move |start| {
+        (0..stacks.len())
+            .filter(move |end| *end != start)
+            .flat_map(move |end| {
+                (1..=stacks[start].len()).map(move |num_crates| Instruction {
+                    num_crates,
+                    start_stack: start + 1,
+                    end_stack: end + 1,
+                })
+            })
+    })
+}
+
+/// Builds `target` from `start` one crate at a time. Needs at least three stacks.
+fn greedy(start: &[Vec<String>], target: &[Vec<String>]) -> Vec<Instruction> {
+    let mut yard = Greedy {
+        stacks: start.to_vec(),
+        target,
+        instructions: Vec::new(),
+    };
+
+    while yard.stacks != target {
+        let (stack, from, position) = yard.cheapest_next_crate();
+
+        if from == stack {
+            // The crate is buried in the stack it needs to go on, so dig it out, put it to one
+            // side, clear the rest of the stack and then put it back.
+            yard.clear_above(stack, position + 1, &[stack]);
+            let aside = yard.put_aside(stack, &[stack]);
+            if yard.settled(aside) == yard.stacks[aside].len() {
+                // It happened to be exactly what the other stack needed.
+                continue;
+            }
+            yard.clear_above(stack, yard.settled(stack), &[stack, aside]);
+            yard.move_crate(aside, stack);
+        } else {
+            yard.clear_above(stack, yard.settled(stack), &[stack, from]);
+            yard.clear_above(from, position + 1, &[stack, from]);
+            yard.move_crate(from, stack);
+        }
+    }
+
+    yard.instructions
+}
+
+struct Greedy<'a> {
+    stacks: Vec<Vec<String>>,
+    target: &'a [Vec<String>],
+    instructions: Vec<Instruction>,
+}
+
+impl Greedy<'_> {
+    /// How many crates from the bottom of `stack` already match the target.
+    fn settled(&self, stack: usize) -> usize {
+        self.stacks[stack]
+            .iter()
+            .zip(&self.target[stack])
+            .take_while(|(current, target)| current == target)
+            .count()
+    }
+
+    /// The stack that can have its next crate put in place with the fewest moves, and where that
+    /// crate is now.
+    fn cheapest_next_crate(&self) -> (usize, usize, usize) {
+        let settled: Vec<usize> = (0..self.stacks.len()).map(|s| self.settled(s)).collect();
+
+        let mut best = None;
+        for (stack, target) in self.target.iter().enumerate() {
+            let Some(needed) = target.get(settled[stack]) else {
+                continue;
+            };
+            let in_the_way = self.stacks[stack].len() - settled[stack];
+
+            for (from, crates) in self.stacks.iter().enumerate() {
+                for (position, label) in crates.iter().enumerate().skip(settled[from]) {
+                    if label != needed {
+                        continue;
+                    }
+                    let cost = if from == stack {
+                        in_the_way + 1
+                    } else {
+                        in_the_way + crates.len() - position
+                    };
+                    if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                        best = Some((cost, (stack, from, position)));
+                    }
+                }
+            }
+        }
+
+        // The crates out of place are exactly the ones the unfinished stacks still need.
+        best.expect("Something is out of place").1
+    }
+
+    /// Moves crates off `stack` until it only has `height` left.
+    fn clear_above(&mut self, stack: usize, height: usize, avoid: &[usize]) {
+        while self.stacks[stack].len() > height {
+            self.put_aside(stack, avoid);
+        }
+    }
+
+    /// Moves the top crate of `stack` somewhere not in `avoid` and returns where it went.
+    ///
+    /// Stacks where it's the next crate needed are best, then stacks that are already finished
+    /// (so nothing is being covered up), then stacks that already have crates out of place.
+    fn put_aside(&mut self, stack: usize, avoid: &[usize]) -> usize {
+        let label = self.stacks[stack]
+            .last()
+            .expect("Only non-empty stacks are cleared");
+        let rank = |other: usize| {
+            let settled = self.settled(other);
+            let clean = settled == self.stacks[other].len();
+            if clean && self.target[other].get(settled) == Some(label) {
+                0
+            } else if settled == self.target[other].len() {
+                1
+            } else if !clean {
+                2
+            } else {
+                3
+            }
+        };
+
+        let other = (0..self.stacks.len())
+            .filter(|other| !avoid.contains(other))
+            .min_by_key(|&other| rank(other))
+            .expect("There are at least three stacks");
+        self.move_crate(stack, other);
+        other
+    }
+
+    fn move_crate(&mut self, from: usize, to: usize) {
+        let label = self.stacks[from]
+            .pop()
+            .expect("Only moves crates that exist");
+        self.stacks[to].push(label);
+        self.instructions.push(Instruction {
+            num_crates: 1,
+            start_stack: from + 1,
+            end_stack: to + 1,
+        });
+    }
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanError::DifferentStackCounts { start, target } => write!(
+                f,
+                "the start has {start} stacks but the target has {target}"
+            ),
+            PlanError::DifferentCrates => write!(f, "the start and target have different crates"),
+            PlanError::Unreachable => write!(f, "the crane can't reach the target"),
+            PlanError::TooManyStates => write!(
+                f,
+                "too many arrangements to search and too few stacks to plan greedily"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::crane::{BatchCrane, CrateMover9000, CrateMover9001};
+    use super::super::tests::example_file;
+    use super::super::yard::CraneYard;
+    use super::super::{drawing, write_input};
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    fn stacks(drawing: &str) -> Vec<Vec<String>> {
+        drawing::parse(drawing).unwrap()
+    }
+
+    /// Feeds the plan back through the normal solver and checks it ends up at `target`.
+    fn check<C: Crane + Copy>(
+        crane: C,
+        start: &[Vec<String>],
+        target: &[Vec<String>],
+        plan: &Plan,
+    ) {
+        let input = write_input(start, &plan.instructions);
+        let mut yard = CraneYard::from_input(&input, crane).unwrap();
+        yard.run().unwrap();
+        assert_eq!(yard.stacks(), target, "{input}");
+    }
+
+    #[test]
+    fn example() {
+        let start = stacks("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n");
+
+        let mut yard = CraneYard::from_input(&example_file(), CrateMover9000).unwrap();
+        yard.run().unwrap();
+        let target = yard.stacks().to_vec();
+
+        let plan = Planner::new(CrateMover9000).plan(&start, &target).unwrap();
+        assert!(plan.shortest);
+        assert!(plan.instructions.len() <= 4);
+        check(CrateMover9000, &start, &target, &plan);
+    }
+
+    #[test]
+    fn shortest_depends_on_the_crane() {
+        let start = stacks("[A]        \n[B]        \n[C]        \n 1   2   3 \n");
+        let target = stacks("        [A]\n        [B]\n        [C]\n 1   2   3 \n");
+
+        let plan = Planner::new(CrateMover9001).plan(&start, &target).unwrap();
+        assert_eq!(
+            plan.instructions,
+            [Instruction {
+                num_crates: 3,
+                start_stack: 1,
+                end_stack: 3,
+            }]
+        );
+
+        // The 9000 flips the stack on every move so it needs a second one to flip it back.
+        let plan = Planner::new(CrateMover9000).plan(&start, &target).unwrap();
+        assert_eq!(plan.instructions.len(), 2);
+        check(CrateMover9000, &start, &target, &plan);
+    }
+
+    #[test]
+    fn greedy_plans() {
+        let mut rng = TestRng::new(99);
+
+        for _ in 0..50 {
+            let num_stacks = 3 + rng.below(3);
+            let crates: Vec<String> = (0..1 + rng.below(12))
+                .map(|_| ((b'A' + rng.below(5) as u8) as char).to_string())
+                .collect();
+            let mut shuffled = crates.clone();
+            shuffled.reverse();
+            shuffled.rotate_left(rng.below(crates.len()));
+
+            let mut scatter = |crates: &[String]| {
+                let mut stacks = vec![Vec::new(); num_stacks];
+                for label in crates {
+                    stacks[rng.below(num_stacks)].push(label.clone());
+                }
+                stacks
+            };
+            let start = scatter(&crates);
+            let target = scatter(&shuffled);
+
+            let plan = Planner::new(CrateMover9000)
+                .max_states(0)
+                .plan(&start, &target)
+                .unwrap();
+            let u = crates.len();
+            assert!(plan.instructions.len() <= u * (u + 3) / 2);
+            check(CrateMover9000, &start, &target, &plan);
+
+            let plan = Planner::new(CrateMover9001)
+                .max_states(0)
+                .plan(&start, &target)
+                .unwrap();
+            check(CrateMover9001, &start, &target, &plan);
+
+            let plan = Planner::new(BatchCrane::new(2))
+                .max_states(0)
+                .plan(&start, &target)
+                .unwrap();
+            check(BatchCrane::new(2), &start, &target, &plan);
+        }
+    }
+
+    #[test]
+    fn greedy_merges_moves_for_the_9000() {
+        let start = stacks("[A]        \n[B]        \n[C]        \n 1   2   3 \n");
+        let target = stacks("        [C]\n        [B]\n        [A]\n 1   2   3 \n");
+
+        let plan = Planner::new(CrateMover9000)
+            .max_states(0)
+            .plan(&start, &target)
+            .unwrap();
+        assert_eq!(
+            plan.instructions,
+            [Instruction {
+                num_crates: 3,
+                start_stack: 1,
+                end_stack: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn impossible_plans() {
+        let planner = Planner::new(CrateMover9000);
+
+        assert_eq!(
+            planner.plan(&stacks("[A]\n 1 \n"), &stacks("[A]    \n 1   2 \n")),
+            Err(PlanError::DifferentStackCounts {
+                start: 1,
+                target: 2,
+            })
+        );
+        assert_eq!(
+            planner.plan(&stacks("[A]\n 1 \n"), &stacks("[B]\n 1 \n")),
+            Err(PlanError::DifferentCrates)
+        );
+
+        // With two stacks the 9000 can only ever pour crates back and forth, so reading the
+        // first stack upwards and then the second downwards always gives the same order.
+        let start = stacks("[B]    \n[A]    \n 1   2 \n");
+        let target = stacks("[A]    \n[B]    \n 1   2 \n");
+        assert_eq!(planner.plan(&start, &target), Err(PlanError::Unreachable));
+        assert_eq!(
+            planner.max_states(1).plan(&start, &target),
+            Err(PlanError::TooManyStates)
+        );
+    }
+}
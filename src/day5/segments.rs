@@ -0,0 +1,379 @@
+use super::crane::Crane;
+use super::{Instruction, LabelTable, MoveError, MoveErrorKind, RearrangeError};
+
+/// Runs the whole procedure like `super::rearrange` but with `SegmentedStacks`, which is much
+/// faster when the instructions move lots of crates at a time.
+pub fn rearrange<C: Crane>(input: &str, crane: C) -> Result<Vec<String>, RearrangeError> {
+    let mut labels = LabelTable::default();
+    let (stacks, instructions) = super::parse_input_with(input, |label| labels.intern(label))?;
+    let mut stacks = SegmentedStacks::new(stacks);
+
+    for (step, instruction) in instructions.iter().enumerate() {
+        stacks
+            .apply(instruction, &crane)
+            .map_err(|kind| MoveError { step, kind })?;
+    }
+
+    Ok(labels.labels(stacks.tops()))
+}
+
+/// A run of crates that are next to each other in `SegmentedStacks::crates`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Segment {
+    start: usize,
+    end: usize,
+    // Whether the crates go from `end - 1` at the bottom down to `start` at the top, rather than
+    // from `start` upwards.
+    reversed: bool,
+}
+
+impl Segment {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn top(&self) -> usize {
+        if self.reversed {
+            self.start
+        } else {
+            self.end - 1
+        }
+    }
+
+    /// Splits the segment into the bottom crates and the top `num_crates` crates.
+    fn split(self, num_crates: usize) -> (Segment, Segment) {
+        let keep = self.len() - num_crates;
+        let (bottom, top) = if self.reversed {
+            (self.end - keep..self.end, self.start..self.end - keep)
+        } else {
+            (self.start..self.start + keep, self.start + keep..self.end)
+        };
+
+        (
+            Segment {
+                start: bottom.start,
+                end: bottom.end,
+                reversed: self.reversed,
+            },
+            Segment {
+                start: top.start,
+                end: top.end,
+                reversed: self.reversed,
+            },
+        )
+    }
+
+    fn flipped(self) -> Segment {
+        Segment {
+            reversed: !self.reversed,
+            ..self
+        }
+    }
+
+    /// One segment with the crates of `above` on top of ours, if they carry on from each other.
+    fn join(self, above: Segment) -> Option<Segment> {
+        // A single crate reads the same either way up.
+        let upwards = |segment: Segment| !segment.reversed || segment.len() == 1;
+        let downwards = |segment: Segment| segment.reversed || segment.len() == 1;
+
+        if upwards(self) && upwards(above) && self.end == above.start {
+            Some(Segment {
+                start: self.start,
+                end: above.end,
+                reversed: false,
+            })
+        } else if downwards(self) && downwards(above) && above.end == self.start {
+            Some(Segment {
+                start: above.start,
+                end: self.end,
+                reversed: true,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Stacks of crates stored as runs of a shared list of crates rather than one list per stack.
+///
+/// Moving crates only splits the run at the bottom of the move and hands the runs above it to the
+/// other stack, so it costs time proportional to the number of runs moved rather than the number
+/// of crates. Cranes that land everything reversed (like the 9000) just flip each run. Any other
+/// crane needs the moved crates copied to the end of the shared list so it can arrange them, and
+/// those copies are never freed, so they're only fast for cranes that report `lands_reversed`.
+#[derive(Clone, Debug)]
+pub struct SegmentedStacks<T> {
+    crates: Vec<T>,
+    // Bottom run first.
+    stacks: Vec<Vec<Segment>>,
+    lens: Vec<usize>,
+}
+
+impl<T: Clone> SegmentedStacks<T> {
+    /// `stacks` are bottom crate first.
+    pub fn new(stacks: Vec<Vec<T>>) -> Self {
+        let mut crates = Vec::new();
+        let mut segments = Vec::with_capacity(stacks.len());
+        let mut lens = Vec::with_capacity(stacks.len());
+
+        for stack in stacks {
+            let start = crates.len();
+            lens.push(stack.len());
+            crates.extend(stack);
+
+            let segment = Segment {
+                start,
+                end: crates.len(),
+                reversed: false,
+            };
+            segments.push(if segment.len() > 0 {
+                vec![segment]
+            } else {
+                Vec::new()
+            });
+        }
+
+        Self {
+            crates,
+            stacks: segments,
+            lens,
+        }
+    }
+
+    /// Does one instruction, leaving everything as it was if it can't be done.
+    pub fn apply<C: Crane>(
+        &mut self,
+        instruction: &Instruction,
+        crane: &C,
+    ) -> Result<(), MoveErrorKind> {
+        let Instruction {
+            num_crates,
+            start_stack,
+            end_stack,
+        } = *instruction;
+        for stack in [start_stack, end_stack] {
+            if stack == 0 || stack > self.stacks.len() {
+                return Err(MoveErrorKind::NoSuchStack(stack));
+            }
+        }
+        if num_crates > self.lens[start_stack - 1] {
+            return Err(MoveErrorKind::NotEnoughCrates {
+                stack: start_stack,
+                needed: num_crates,
+                available: self.lens[start_stack - 1],
+            });
+        }
+
+        let mut lifted = self.lift(start_stack - 1, num_crates);
+        match crane.lands_reversed() {
+            Some(true) => {
+                lifted.reverse();
+                lifted
+                    .iter_mut()
+                    .for_each(|segment| *segment = segment.flipped());
+            }
+            Some(false) => {}
+            None => {
+                let mut crates: Vec<T> = lifted
+                    .iter()
+                    .flat_map(|segment| self.segment_crates(*segment))
+                    .cloned()
+                    .collect();
+                crane.arrange(&mut crates);
+
+                let start = self.crates.len();
+                self.crates.extend(crates);
+                lifted = vec![Segment {
+                    start,
+                    end: self.crates.len(),
+                    reversed: false,
+                }];
+            }
+        }
+        self.land(end_stack - 1, lifted, num_crates);
+
+        Ok(())
+    }
+
+    /// The label of the crate on top of each stack, skipping empty stacks.
+    pub fn tops(&self) -> Vec<T> {
+        self.stacks
+            .iter()
+            .filter_map(|stack| stack.last())
+            .map(|segment| self.crates[segment.top()].clone())
+            .collect()
+    }
+
+    /// Copies out every stack, bottom crate first.
+    pub fn to_stacks(&self) -> Vec<Vec<T>> {
+        self.stacks
+            .iter()
+            .map(|stack| {
+                stack
+                    .iter()
+                    .flat_map(|segment| self.segment_crates(*segment))
+                    .cloned()
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The crates in `segment`, bottom first.
+    fn segment_crates(&self, segment: Segment) -> impl Iterator<Item = &T> {
+        let crates = &self.crates[segment.start..segment.end];
+        let (forwards, backwards) = if segment.reversed {
+            (None, Some(crates.iter().rev()))
+        } else {
+            (Some(crates.iter()), None)
+        };
+        forwards
+            .into_iter()
+            .flatten()
+            .chain(backwards.into_iter().flatten())
+    }
+
+    /// Takes the top `num_crates` crates off `stack` as segments, bottom first.
+    fn lift(&mut self, stack: usize, num_crates: usize) -> Vec<Segment> {
+        let segments = &mut self.stacks[stack];
+        let mut remaining = num_crates;
+        let mut idx = segments.len();
+
+        while remaining > 0 {
+            idx -= 1;
+            let len = segments[idx].len();
+            if len > remaining {
+                let (bottom, top) = segments[idx].split(remaining);
+                segments[idx] = bottom;
+                segments.insert(idx + 1, top);
+                idx += 1;
+                break;
+            }
+            remaining -= len;
+        }
+
+        self.lens[stack] -= num_crates;
+        segments.split_off(idx)
+    }
+
+    fn land(&mut self, stack: usize, segments: Vec<Segment>, num_crates: usize) {
+        let stack_segments = &mut self.stacks[stack];
+        for segment in segments {
+            match stack_segments.last_mut() {
+                Some(top) => match top.join(segment) {
+                    Some(joined) => *top = joined,
+                    None => stack_segments.push(segment),
+                },
+                None => stack_segments.push(segment),
+            }
+        }
+        self.lens[stack] += num_crates;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::crane::{AlternatingCrane, BatchCrane, CrateMover9000, CrateMover9001};
+    use super::super::tests::example_file;
+    use super::super::yard::CraneYard;
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    #[test]
+    fn example() {
+        assert_eq!(
+            rearrange(&example_file(), CrateMover9000).map(|tops| tops.concat()),
+            Ok("CMZ".into())
+        );
+        assert_eq!(
+            rearrange(&example_file(), CrateMover9001).map(|tops| tops.concat()),
+            Ok("MCD".into())
+        );
+        assert_eq!(
+            rearrange(&example_file(), BatchCrane::new(2)).map(|tops| tops.concat()),
+            Ok("MCZ".into())
+        );
+    }
+
+    #[test]
+    fn matches_vec_stacks() {
+        let mut rng = TestRng::new(7);
+
+        let stacks: Vec<Vec<String>> = (0..5)
+            .map(|_| (0..20).map(|_| rng.below(1000).to_string()).collect())
+            .collect();
+        let mut instructions = Vec::new();
+        let mut lens = [20; 5];
+        for _ in 0..500 {
+            let start = rng.below(5);
+            let end = rng.below(5);
+            let num_crates = rng.below(lens[start] + 1);
+            lens[start] -= num_crates;
+            lens[end] += num_crates;
+            instructions.push(Instruction {
+                num_crates,
+                start_stack: start + 1,
+                end_stack: end + 1,
+            });
+        }
+
+        fn check<C: Crane + Copy>(crane: C, stacks: &[Vec<String>], instructions: &[Instruction]) {
+            let mut yard = CraneYard::new(stacks.to_vec(), instructions.to_vec(), crane);
+            let mut segmented = SegmentedStacks::new(stacks.to_vec());
+
+            for instruction in instructions {
+                yard.step_forward().unwrap();
+                segmented.apply(instruction, &crane).unwrap();
+                assert_eq!(segmented.to_stacks(), yard.stacks());
+            }
+            assert_eq!(segmented.tops(), yard.tops());
+        }
+
+        check(CrateMover9000, &stacks, &instructions);
+        check(CrateMover9001, &stacks, &instructions);
+        check(BatchCrane::new(3), &stacks, &instructions);
+        check(AlternatingCrane::new(2), &stacks, &instructions);
+    }
+
+    #[test]
+    fn runs_join_back_together() {
+        let labels = |crates: &str| crates.chars().map(String::from).collect::<Vec<_>>();
+        let mut stacks = SegmentedStacks::new(vec![labels("ABCDEF"), Vec::new()]);
+
+        // Moving crates one at a time with the 9000 reads them back off in reverse, which is one
+        // run going downwards.
+        let one = Instruction {
+            num_crates: 1,
+            start_stack: 1,
+            end_stack: 2,
+        };
+        for _ in 0..4 {
+            stacks.apply(&one, &CrateMover9000).unwrap();
+        }
+        assert_eq!(stacks.to_stacks(), [labels("AB"), labels("FEDC")]);
+        assert_eq!(stacks.stacks[1].len(), 1);
+    }
+
+    #[test]
+    fn invalid_instructions_change_nothing() {
+        let mut stacks = SegmentedStacks::new(vec![vec!["A".to_string()], Vec::new()]);
+        let instruction = |num_crates, start_stack, end_stack| Instruction {
+            num_crates,
+            start_stack,
+            end_stack,
+        };
+
+        assert_eq!(
+            stacks.apply(&instruction(2, 1, 2), &CrateMover9000),
+            Err(MoveErrorKind::NotEnoughCrates {
+                stack: 1,
+                needed: 2,
+                available: 1,
+            })
+        );
+        assert_eq!(
+            stacks.apply(&instruction(1, 1, 3), &CrateMover9000),
+            Err(MoveErrorKind::NoSuchStack(3))
+        );
+        assert_eq!(stacks.to_stacks(), [vec!["A".to_string()], Vec::new()]);
+    }
+}
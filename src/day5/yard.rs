@@ -0,0 +1,235 @@
+use super::crane::Crane;
+use super::{Instruction, MoveError, RearrangeError};
+
+/// A rearrangement procedure that can be stepped through in either direction.
+///
+/// Every step remembers the crates it lifted so undoing it puts exactly those crates back, no
+/// matter what order the crane left them in.
+#[derive(Clone, Debug)]
+pub struct CraneYard<C> {
+    stacks: Vec<Vec<String>>,
+    crane: C,
+    instructions: Vec<Instruction>,
+    // The crates lifted by each step we've done so far, bottom first, as they were before moving.
+    history: Vec<Vec<String>>,
+}
+
+impl<C: Crane> CraneYard<C> {
+    /// `stacks` are bottom crate first.
+    pub fn new(stacks: Vec<Vec<String>>, instructions: Vec<Instruction>, crane: C) -> Self {
+        Self {
+            stacks,
+            crane,
+            instructions,
+            history: Vec::new(),
+        }
+    }
+
+    /// Reads the drawing and instructions. Moves aren't checked until they're done.
+    pub fn from_input(input: &str, crane: C) -> Result<Self, RearrangeError> {
+        let (stacks, instructions) = super::parse_input(input)?;
+        Ok(Self::new(stacks, instructions, crane))
+    }
+
+    /// How many instructions have been done.
+    pub fn position(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// The current state of every stack, bottom crate first.
+    pub fn stacks(&self) -> &[Vec<String>] {
+        &self.stacks
+    }
+
+    /// The current state of stack `number` (1 indexed), bottom crate first.
+    pub fn stack(&self, number: usize) -> Option<&[String]> {
+        let idx = number.checked_sub(1)?;
+        self.stacks.get(idx).map(Vec::as_slice)
+    }
+
+    /// The label of the crate on top of each stack, skipping empty stacks.
+    pub fn tops(&self) -> Vec<String> {
+        super::tops(&self.stacks)
+    }
+
+    /// The current state of the stacks drawn like the puzzle input.
+    pub fn drawing(&self) -> String {
+        super::drawing::render(&self.stacks)
+    }
+
+    /// Does the next instruction. Returns `false` if there aren't any left.
+    pub fn step_forward(&mut self) -> Result<bool, MoveError> {
+        let step = self.position();
+        let Some(instruction) = self.instructions.get(step).copied() else {
+            return Ok(false);
+        };
+
+        let mut crates =
+            super::lift(&mut self.stacks, &instruction).map_err(|kind| MoveError { step, kind })?;
+        self.history.push(crates.clone());
+        self.crane.arrange(&mut crates);
+        self.stacks[instruction.end_stack - 1].extend(crates);
+
+        Ok(true)
+    }
+
+    /// Undoes the last instruction. Returns `false` if we're already at the start.
+    pub fn step_back(&mut self) -> bool {
+        let Some(crates) = self.history.pop() else {
+            return false;
+        };
+        let instruction = self.instructions[self.history.len()];
+
+        let end = &mut self.stacks[instruction.end_stack - 1];
+        end.truncate(end.len() - crates.len());
+        self.stacks[instruction.start_stack - 1].extend(crates);
+
+        true
+    }
+
+    /// Steps forward or back until exactly `position` instructions have been done.
+    pub fn jump_to(&mut self, position: usize) -> Result<(), MoveError> {
+        let position = position.min(self.instructions.len());
+        while self.position() > position {
+            self.step_back();
+        }
+        while self.position() < position {
+            self.step_forward()?;
+        }
+        Ok(())
+    }
+
+    /// Does every remaining instruction.
+    pub fn run(&mut self) -> Result<(), MoveError> {
+        self.jump_to(self.instructions.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::crane::{CrateMover9000, CrateMover9001};
+    use super::super::tests::example_file;
+    use super::super::MoveErrorKind;
+    use super::*;
+
+    fn labels(crates: &str) -> Vec<String> {
+        crates.chars().map(String::from).collect()
+    }
+
+    #[test]
+    fn run_to_end() {
+        let mut yard = CraneYard::from_input(&example_file(), CrateMover9000).unwrap();
+        yard.run().unwrap();
+        assert_eq!(yard.tops().concat(), "CMZ");
+
+        let mut yard = CraneYard::from_input(&example_file(), CrateMover9001).unwrap();
+        yard.run().unwrap();
+        assert_eq!(yard.tops().concat(), "MCD");
+    }
+
+    #[test]
+    fn step_forward_and_back() {
+        let mut yard = CraneYard::from_input(&example_file(), CrateMover9000).unwrap();
+        let start = yard.stacks().to_vec();
+
+        assert!(yard.step_forward().unwrap());
+        assert_eq!(yard.position(), 1);
+        assert_eq!(yard.stack(1), Some(&labels("ZND")[..]));
+        assert_eq!(yard.stack(2), Some(&labels("MC")[..]));
+
+        assert!(yard.step_forward().unwrap());
+        assert_eq!(yard.stack(1), Some(&labels("")[..]));
+        assert_eq!(yard.stack(3), Some(&labels("PDNZ")[..]));
+
+        assert!(yard.step_back());
+        assert_eq!(yard.stack(1), Some(&labels("ZND")[..]));
+        assert!(yard.step_back());
+        assert_eq!(yard.stacks(), start);
+        assert!(!yard.step_back());
+    }
+
+    #[test]
+    fn jump_to() {
+        let mut yard = CraneYard::from_input(&example_file(), CrateMover9001).unwrap();
+
+        yard.jump_to(3).unwrap();
+        assert_eq!(yard.position(), 3);
+        assert_eq!(yard.stack(1), Some(&labels("MC")[..]));
+        assert_eq!(yard.stack(2), Some(&labels("")[..]));
+        assert_eq!(yard.stack(3), Some(&labels("PZND")[..]));
+
+        yard.jump_to(1).unwrap();
+        assert_eq!(yard.stack(1), Some(&labels("ZND")[..]));
+
+        // Past the end just runs everything.
+        yard.jump_to(100).unwrap();
+        assert_eq!(yard.position(), 4);
+        assert_eq!(yard.tops().concat(), "MCD");
+        assert!(!yard.step_forward().unwrap());
+        assert_eq!(
+            yard.drawing(),
+            "        [D]\n\
+             \x20       [N]\n\
+             \x20       [Z]\n\
+             [M] [C] [P]\n \
+             1   2   3 \n"
+        );
+    }
+
+    #[test]
+    fn invalid_instructions() {
+        let instruction = |num_crates, start_stack, end_stack| Instruction {
+            num_crates,
+            start_stack,
+            end_stack,
+        };
+        let stacks = vec![labels("AB"), labels("C")];
+
+        let mut yard = CraneYard::new(
+            stacks.clone(),
+            vec![instruction(1, 1, 2), instruction(3, 1, 2)],
+            CrateMover9000,
+        );
+        assert_eq!(
+            yard.run(),
+            Err(MoveError {
+                step: 1,
+                kind: MoveErrorKind::NotEnoughCrates {
+                    stack: 1,
+                    needed: 3,
+                    available: 1,
+                },
+            })
+        );
+        // The first step still happened.
+        assert_eq!(yard.position(), 1);
+
+        let mut yard = CraneYard::new(stacks, vec![instruction(1, 3, 1)], CrateMover9000);
+        assert_eq!(
+            yard.step_forward(),
+            Err(MoveError {
+                step: 0,
+                kind: MoveErrorKind::NoSuchStack(3),
+            })
+        );
+    }
+
+    #[test]
+    fn same_stack() {
+        let instruction = Instruction {
+            num_crates: 2,
+            start_stack: 1,
+            end_stack: 1,
+        };
+        let mut yard = CraneYard::new(vec![labels("ABC")], vec![instruction], CrateMover9000);
+
+        yard.run().unwrap();
+        assert_eq!(yard.stack(1), Some(&labels("ACB")[..]));
+        yard.step_back();
+        assert_eq!(yard.stack(1), Some(&labels("ABC")[..]));
+    }
+}
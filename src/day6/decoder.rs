@@ -0,0 +1,363 @@
+//! Decoding a signal as it arrives instead of all at once.
+//!
+//! After a marker is found its detector starts again from scratch, so every marker is made of
+//! characters that come after the previous marker of the same kind.
+
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind, Read};
+
+/// Something found in the stream. Positions are the number of characters read by the end of the
+/// marker, like the puzzle answers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    StartOfPacket(usize),
+    StartOfMessage(usize),
+}
+
+/// Spots runs of `window_len` different bytes one byte at a time.
+#[derive(Clone, Debug)]
+pub struct MarkerDetector {
+    // The last `filled` bytes, oldest at `head` once it's full.
+    window: Vec<u8>,
+    head: usize,
+    filled: usize,
+    counts: [u32; 256],
+    // How many bytes are in the window more than once.
+    repeats: usize,
+}
+
+impl MarkerDetector {
+    pub fn new(window_len: usize) -> Self {
+        assert!(window_len > 0, "Markers need at least one character");
+        Self {
+            window: vec![0; window_len],
+            head: 0,
+            filled: 0,
+            counts: [0; 256],
+            repeats: 0,
+        }
+    }
+
+    /// Adds the next byte and says whether it finished a marker. Starts looking for a new marker
+    /// if it did.
+    pub fn push(&mut self, byte: u8) -> bool {
+        let window_len = self.window.len();
+        if self.filled == window_len {
+            let leaving = self.window[self.head] as usize;
+            self.counts[leaving] -= 1;
+            if self.counts[leaving] == 1 {
+                self.repeats -= 1;
+            }
+        } else {
+            self.filled += 1;
+        }
+
+        self.window[self.head] = byte;
+        self.head = (self.head + 1) % window_len;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.repeats += 1;
+        }
+
+        let found = self.filled == window_len && self.repeats == 0;
+        if found {
+            self.reset();
+        }
+        found
+    }
+
+    pub fn reset(&mut self) {
+        for &byte in &self.window[..self.filled] {
+            self.counts[byte as usize] -= 1;
+        }
+        self.head = 0;
+        self.filled = 0;
+        self.repeats = 0;
+    }
+}
+
+/// Finds every start-of-packet and start-of-message marker in a signal fed to it in chunks of
+/// any size.
+#[derive(Clone, Debug)]
+pub struct Decoder {
+    packet: MarkerDetector,
+    message: MarkerDetector,
+    position: usize,
+}
+
+impl Decoder {
+    /// Uses the puzzle's marker lengths: 4 for packets and 14 for messages.
+    pub fn new() -> Self {
+        Self::with_lengths(4, 14)
+    }
+
+    pub fn with_lengths(packet_len: usize, message_len: usize) -> Self {
+        Self {
+            packet: MarkerDetector::new(packet_len),
+            message: MarkerDetector::new(message_len),
+            position: 0,
+        }
+    }
+
+    /// How many bytes have been fed in so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Feeds in the next chunk of the signal and returns the markers that end inside it.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            self.position += 1;
+            if self.packet.push(byte) {
+                events.push(Event::StartOfPacket(self.position));
+            }
+            if self.message.push(byte) {
+                events.push(Event::StartOfMessage(self.position));
+            }
+        }
+        events
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Every event in the signal read from `reader`, read a chunk at a time.
+pub fn events<R: Read>(reader: R) -> Events<R> {
+    Events {
+        reader,
+        decoder: Decoder::new(),
+        pending: VecDeque::new(),
+        finished: false,
+    }
+}
+
+pub struct Events<R> {
+    reader: R,
+    decoder: Decoder,
+    pending: VecDeque<Event>,
+    finished: bool,
+}
+
+impl<R> Events<R> {
+    /// How many bytes have been read so far.
+    pub fn position(&self) -> usize {
+        self.decoder.position()
+    }
+}
+
+impl<R: Read> Iterator for Events<R> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = [0; 4096];
+        while self.pending.is_empty() && !self.finished {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => self.finished = true,
+                Ok(len) => self.pending.extend(self.decoder.feed(&chunk[..len])),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Where the first start-of-packet marker in `reader` ends, or `None` if there isn't one.
+pub fn first_packet<R: Read>(reader: R) -> io::Result<Option<usize>> {
+    first(reader, |event| match event {
+        Event::StartOfPacket(position) => Some(position),
+        Event::StartOfMessage(_) => None,
+    })
+}
+
+/// Where the first start-of-message marker in `reader` ends, or `None` if there isn't one.
+pub fn first_message<R: Read>(reader: R) -> io::Result<Option<usize>> {
+    first(reader, |event| match event {
+        Event::StartOfMessage(position) => Some(position),
+        Event::StartOfPacket(_) => None,
+    })
+}
+
+fn first<R: Read>(
+    reader: R,
+    mut wanted: impl FnMut(Event) -> Option<usize>,
+) -> io::Result<Option<usize>> {
+    for event in events(reader) {
+        if let Some(position) = wanted(event?) {
+            return Ok(Some(position));
+        }
+    }
+    Ok(None)
+}
+
+/// Splits the signal read from `reader` into messages: everything after each start-of-message
+/// marker of `message_len` characters, up to the start of the next marker or the end of the
+/// signal. Anything before the first marker isn't part of a message.
+pub fn messages<R: Read>(mut reader: R, message_len: usize) -> io::Result<Vec<Vec<u8>>> {
+    let mut detector = MarkerDetector::new(message_len);
+    let mut messages = Vec::new();
+    // `None` until the first marker.
+    let mut current: Option<Vec<u8>> = None;
+
+    let mut chunk = [0; 4096];
+    loop {
+        let len = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        for &byte in &chunk[..len] {
+            if let Some(message) = current.as_mut() {
+                message.push(byte);
+            }
+            if detector.push(byte) {
+                if let Some(mut message) = current.take() {
+                    // The marker itself isn't part of the message before it.
+                    message.truncate(message.len() - message_len);
+                    messages.push(message);
+                }
+                current = Some(Vec::new());
+            }
+        }
+    }
+
+    messages.extend(current);
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out a few bytes per read and gets interrupted every other time.
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        chunk_len: usize,
+        interrupt: bool,
+    }
+
+    impl<'a> Trickle<'a> {
+        fn new(bytes: &'a str, chunk_len: usize) -> Self {
+            Self {
+                bytes: bytes.as_bytes(),
+                chunk_len,
+                interrupt: false,
+            }
+        }
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(ErrorKind::Interrupted.into());
+            }
+
+            let len = self.chunk_len.min(buf.len()).min(self.bytes.len());
+            let (chunk, rest) = self.bytes.split_at(len);
+            buf[..len].copy_from_slice(chunk);
+            self.bytes = rest;
+            Ok(len)
+        }
+    }
+
+    const EXAMPLE: &str = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+
+    #[test]
+    fn first_markers_match_the_puzzle() {
+        assert_eq!(first_packet(EXAMPLE.as_bytes()).unwrap(), Some(7));
+        assert_eq!(first_message(EXAMPLE.as_bytes()).unwrap(), Some(19));
+
+        assert_eq!(first_packet(Trickle::new(EXAMPLE, 3)).unwrap(), Some(7));
+        assert_eq!(first_message(Trickle::new(EXAMPLE, 1)).unwrap(), Some(19));
+    }
+
+    #[test]
+    fn every_marker() {
+        let all: Vec<Event> = events(EXAMPLE.as_bytes()).map(Result::unwrap).collect();
+
+        assert_eq!(
+            all,
+            [
+                Event::StartOfPacket(7),
+                Event::StartOfPacket(11),
+                Event::StartOfPacket(15),
+                Event::StartOfPacket(19),
+                Event::StartOfMessage(19),
+                Event::StartOfPacket(23),
+                Event::StartOfPacket(27),
+            ]
+        );
+
+        // Chunk boundaries don't matter.
+        for chunk_len in 1..=EXAMPLE.len() {
+            let trickled: Vec<Event> = events(Trickle::new(EXAMPLE, chunk_len))
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(trickled, all, "chunks of {chunk_len}");
+        }
+    }
+
+    #[test]
+    fn no_markers() {
+        assert_eq!(first_packet("abcabcabc".as_bytes()).unwrap(), None);
+        assert_eq!(first_message(EXAMPLE.as_bytes().take(18)).unwrap(), None);
+        assert_eq!(first_packet(io::empty()).unwrap(), None);
+        assert_eq!(
+            messages("aaaa".as_bytes(), 2).unwrap(),
+            Vec::<Vec<u8>>::new()
+        );
+    }
+
+    #[test]
+    fn read_errors_are_returned() {
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("unplugged"))
+            }
+        }
+
+        assert!(first_packet(Broken).is_err());
+        assert!(messages(Broken, 4).is_err());
+
+        let mut events = events(Broken);
+        assert!(events.next().unwrap().is_err());
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn splits_messages() {
+        // Each message ends with the first letter of the next marker so the marker can't start
+        // any earlier.
+        let signal = "aaaabcd xxee efgh zzii ijkl oo".replace(' ', "");
+
+        assert_eq!(
+            messages(Trickle::new(&signal, 2), 4).unwrap(),
+            [b"xxee".to_vec(), b"zzii".to_vec(), b"oo".to_vec()]
+        );
+    }
+
+    #[test]
+    fn decoder_keeps_its_place() {
+        let mut decoder = Decoder::with_lengths(2, 3);
+
+        assert_eq!(decoder.feed(b"aa"), []);
+        assert_eq!(decoder.feed(b"b"), [Event::StartOfPacket(3)]);
+        assert_eq!(
+            decoder.feed(b"cd"),
+            [Event::StartOfMessage(4), Event::StartOfPacket(5)]
+        );
+        assert_eq!(decoder.position(), 5);
+    }
+}
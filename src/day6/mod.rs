@@ -1,49 +1,90 @@
+pub mod decoder;
+
 pub fn part1(input: &str) -> usize {
-    let window_length = 4;
-    let start_of_first_unique_window = input
-        .as_bytes()
-        .windows(window_length)
-        .position(|window| {
-            let mut items = window
-                .try_into()
-                .expect("windows() should return the correct size slice");
-            is_unique_short(&mut items)
-        })
-        .expect("Should be an answer!");
-    start_of_first_unique_window + window_length
+    find_marker(input, 4).expect("Should be an answer!")
 }
 
-// Did some invalid benchtesting on:
-// https://play.rust-lang.org/?version=stable&mode=release&edition=2021&gist=92b9bab94615b6f3191db1386c2daee4
-// and it seems like this is the way to go :shrug:
-fn is_unique_short(items: &mut [u8; 4]) -> bool {
-    items.sort_unstable();
-    items.windows(2).all(|window| window[0] != window[1])
+pub fn part2(input: &str) -> usize {
+    find_marker(input, 14).expect("Should be an answer!")
 }
 
-pub fn part2(input: &str) -> usize {
-    let window_length = 14;
-    let start_of_first_unique_window = input
-        .as_bytes()
-        .windows(window_length)
-        .position(|window| {
-            let mut items = window
-                .try_into()
-                .expect("windows() should return the correct size slice");
-            is_unique_long(&mut items)
-        })
-        .expect("Should be an answer!");
-    start_of_first_unique_window + window_length
+/// The number of characters read by the end of the first `window_len` characters in a row that
+/// are all different.
+///
+/// Keeps a count of every byte in the window and how many bytes are in there more than once, so
+/// each character is only looked at when it enters and when it leaves the window.
+pub fn find_marker(input: &str, window_len: usize) -> Option<usize> {
+    let bytes = input.as_bytes();
+    if window_len == 0 {
+        return Some(0);
+    }
+
+    let mut counts = [0u32; 256];
+    let mut repeats = 0;
+    for (idx, &byte) in bytes.iter().enumerate() {
+        counts[byte as usize] += 1;
+        if counts[byte as usize] == 2 {
+            repeats += 1;
+        }
+
+        if let Some(leaving) = idx.checked_sub(window_len) {
+            let leaving = bytes[leaving] as usize;
+            counts[leaving] -= 1;
+            if counts[leaving] == 1 {
+                repeats -= 1;
+            }
+        }
+
+        if idx + 1 >= window_len && repeats == 0 {
+            return Some(idx + 1);
+        }
+    }
+
+    None
 }
 
-// TODO should this be const generic? Should we switch to a HashSet (or similar) when the number
-// gets large? For something like 14 items should we even use `.windows()` this way? I feel like
-// it'd be better to maintain a single `HashSet` and pop off the first item and push on the last
-// item each iteration. But I don't feel like adding a dependency yet... even though hashbrown is
-// already in std. Wow I'm lazy.
-fn is_unique_long(items: &mut [u8; 14]) -> bool {
-    items.sort_unstable();
-    items.windows(2).all(|window| window[0] != window[1])
+/// Same as `find_marker` but remembers where each byte was last seen instead, so whenever a byte
+/// repeats the window can jump straight past its last copy.
+pub fn find_marker_skipping(input: &str, window_len: usize) -> Option<usize> {
+    if window_len == 0 {
+        return Some(0);
+    }
+
+    // 1 indexed so 0 means "never seen".
+    let mut last_seen = [0usize; 256];
+    // 1 indexed start of the window of distinct bytes that ends at the current byte.
+    let mut start = 1;
+    for (idx, &byte) in input.as_bytes().iter().enumerate() {
+        let position = idx + 1;
+        start = start.max(last_seen[byte as usize] + 1);
+        last_seen[byte as usize] = position;
+
+        if position + 1 - start == window_len {
+            return Some(position);
+        }
+    }
+
+    None
+}
+
+/// The original approach: sort a copy of every window and look for neighbours that match. It's
+/// kept as a benchmark baseline next to `find_marker` and `find_marker_skipping`.
+///
+/// Panics if `N` is 0.
+pub fn find_marker_sorting<const N: usize>(input: &str) -> Option<usize> {
+    assert!(N > 0, "Window needs at least one character");
+    let is_unique = |items: &mut [u8; N]| {
+        items.sort_unstable();
+        items.windows(2).all(|window| window[0] != window[1])
+    };
+
+    let start_of_first_unique_window = input.as_bytes().windows(N).position(|window| {
+        let mut items = window
+            .try_into()
+            .expect("windows() should return the correct size slice");
+        is_unique(&mut items)
+    })?;
+    Some(start_of_first_unique_window + N)
 }
 
 #[cfg(test)]
@@ -93,4 +134,47 @@ mod tests {
             assert_eq!(part2(INPUT), 3708);
         }
     }
+
+    mod find_marker {
+        use super::*;
+
+        #[test]
+        fn all_approaches_agree() {
+            let examples = [EXAMPLE1, EXAMPLE2, EXAMPLE3, EXAMPLE4, EXAMPLE5];
+            for example in examples {
+                for window_len in 1..=20 {
+                    let expected = find_marker(example, window_len);
+                    assert_eq!(find_marker_skipping(example, window_len), expected);
+                }
+                assert_eq!(find_marker_sorting::<4>(example), find_marker(example, 4));
+                assert_eq!(find_marker_sorting::<14>(example), find_marker(example, 14));
+            }
+        }
+
+        #[test]
+        fn edge_cases() {
+            assert_eq!(find_marker("", 4), None);
+            assert_eq!(find_marker("abc", 4), None);
+            assert_eq!(find_marker("aaaaaaa", 2), None);
+            assert_eq!(find_marker("abcd", 4), Some(4));
+            assert_eq!(find_marker("aaab", 2), Some(4));
+            assert_eq!(find_marker("xyz", 1), Some(1));
+            assert_eq!(find_marker("xyz", 0), Some(0));
+
+            assert_eq!(find_marker_skipping("aaaaaaa", 2), None);
+            assert_eq!(find_marker_skipping("abab", 3), None);
+            assert_eq!(find_marker_skipping("abcabcd", 4), Some(7));
+        }
+
+        #[test]
+        fn wider_than_the_puzzle() {
+            let stream = "abcdefghijklmnopqrstuvwxyaz";
+
+            // The first 26 have two 'a's but dropping the first one fixes that.
+            assert_eq!(find_marker(stream, 26), Some(27));
+            assert_eq!(find_marker_skipping(stream, 26), Some(27));
+            assert_eq!(find_marker(stream, 25), Some(25));
+            assert_eq!(find_marker(stream, 27), None);
+        }
+    }
 }
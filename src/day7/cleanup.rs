@@ -0,0 +1,387 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::fs::{FileSystem, NodeId, Order, Path};
+
+/// Works out which directories to delete to free up enough space on a disk.
+///
+/// It finds the directories that free enough space while deleting as few bytes as possible. The
+/// directories are never nested, because deleting a directory already deletes everything in it.
+/// The root can't be deleted.
+///
+/// This is a knapsack problem over the tree. Each directory keeps the sorted list of different
+/// totals that can be freed inside it. Sums that are still short of the target are all kept, but
+/// only the smallest one that reaches the target is kept. Lists are combined from the children
+/// up, and the plan is then traced back down. The lists can get long when there are lots of
+/// small directories, so planning gives up once any list grows past `max_states`.
+#[derive(Clone, Debug)]
+pub struct CleanupPlanner {
+    capacity: usize,
+    target_free: usize,
+    protected: Vec<Path>,
+    max_states: usize,
+}
+
+/// The directories to delete, in the order they appear in the tree, and how much space that
+/// frees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cleanup {
+    pub directories: Vec<Path>,
+    pub freed: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CleanupError {
+    /// Deleting every directory that isn't protected still wouldn't free enough space.
+    NotEnoughSpace {
+        needed: usize,
+        available: usize,
+    },
+    TooManyStates,
+}
+
+impl CleanupPlanner {
+    /// Uses the puzzle's numbers: a 70,000,000 byte disk that needs 30,000,000 bytes free.
+    pub fn new() -> Self {
+        Self {
+            capacity: 70_000_000,
+            target_free: 30_000_000,
+            protected: Vec::new(),
+            max_states: 100_000,
+        }
+    }
+
+    /// The size of the disk.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// How much free space there should be after the cleanup.
+    pub fn target_free(mut self, target_free: usize) -> Self {
+        self.target_free = target_free;
+        self
+    }
+
+    /// Keeps `path` and everything inside it. No directory that contains it gets deleted either.
+    /// Paths that aren't in the file system are ignored.
+    pub fn protect(mut self, path: Path) -> Self {
+        self.protected.push(path);
+        self
+    }
+
+    /// How long the list of possible totals for one directory can get before planning gives up.
+    pub fn max_states(mut self, max_states: usize) -> Self {
+        self.max_states = max_states;
+        self
+    }
+
+    /// How many bytes need deleting to reach the target, which is 0 if there's already enough
+    /// free space.
+    pub fn space_needed(&self, fs: &FileSystem) -> usize {
+        let used = fs.total_size(fs.root());
+        (used + self.target_free).saturating_sub(self.capacity)
+    }
+
+    pub fn plan(&self, fs: &FileSystem) -> Result<Cleanup, CleanupError> {
+        let needed = self.space_needed(fs);
+        if needed == 0 {
+            return Ok(Cleanup {
+                directories: Vec::new(),
+                freed: 0,
+            });
+        }
+
+        let mut protected = HashSet::new();
+        // Directories that hold something protected, which can't be deleted as a whole.
+        let mut pinned = HashSet::new();
+        for id in self.protected.iter().filter_map(|path| fs.lookup(path)) {
+            protected.insert(id);
+            let mut current = Some(id);
+            while let Some(id) = current {
+                if !pinned.insert(id) {
+                    break;
+                }
+                current = fs.parent(id);
+            }
+        }
+        let deletable = |id: NodeId| id != fs.root() && !pinned.contains(&id);
+
+        let mut totals: HashMap<NodeId, Vec<usize>> = HashMap::new();
+        for (dir, _, size) in fs.directories(Order::PostOrder) {
+            let id = dir.id();
+            let mut dir_totals = vec![0];
+            if !protected.contains(&id) {
+                for child in child_dirs(fs, id) {
+                    dir_totals = combine(&dir_totals, &totals[&child], needed);
+                    if dir_totals.len() > self.max_states {
+                        return Err(CleanupError::TooManyStates);
+                    }
+                }
+                if deletable(id) {
+                    if let Err(idx) = dir_totals.binary_search(&size) {
+                        dir_totals.insert(idx, size);
+                        dir_totals.truncate(reaching(&dir_totals, needed) + 1);
+                    }
+                }
+            }
+            totals.insert(id, dir_totals);
+        }
+
+        let root_totals = &totals[&fs.root()];
+        let freed = match root_totals.last() {
+            Some(&freed) if freed >= needed => freed,
+            available => {
+                return Err(CleanupError::NotEnoughSpace {
+                    needed,
+                    available: available.copied().unwrap_or(0),
+                })
+            }
+        };
+
+        // Work out which choices inside each directory add up to what it has to free.
+        let mut directories = Vec::new();
+        let mut stack = vec![(fs.root(), freed)];
+        while let Some((id, mut to_free)) = stack.pop() {
+            if to_free == 0 {
+                continue;
+            }
+            if deletable(id) && to_free == fs.total_size(id) {
+                directories.push(fs.path(id));
+                continue;
+            }
+
+            let children: Vec<NodeId> = child_dirs(fs, id).collect();
+            let mut prefixes = vec![vec![0]];
+            for child in &children[..children.len() - 1] {
+                let next = combine(prefixes.last().unwrap(), &totals[child], needed);
+                prefixes.push(next);
+            }
+
+            let mut chosen = Vec::with_capacity(children.len());
+            for (child, before) in children.iter().zip(&prefixes).rev() {
+                let from_child = totals[child]
+                    .iter()
+                    .copied()
+                    .find(|&total| {
+                        total <= to_free && before.binary_search(&(to_free - total)).is_ok()
+                    })
+                    .expect("Every total is made of totals from the directories inside");
+                chosen.push((*child, from_child));
+                to_free -= from_child;
+            }
+            // `chosen` is last child first, so the first child is popped first.
+            stack.extend(chosen);
+        }
+
+        Ok(Cleanup { directories, freed })
+    }
+}
+
+impl Default for CleanupPlanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn child_dirs(fs: &FileSystem, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    fs.children(id).filter(|&child| fs.is_dir(child))
+}
+
+/// Every total made of one from `a` and one from `b`, keeping only the first that reaches
+/// `needed`.
+fn combine(a: &[usize], b: &[usize], needed: usize) -> Vec<usize> {
+    let mut totals: Vec<usize> = a
+        .iter()
+        .flat_map(|&x| b.iter().map(move |&y| x + y))
+        .collect();
+    totals.sort_unstable();
+    totals.dedup();
+    totals.truncate(reaching(&totals, needed) + 1);
+    totals
+}
+
+/// The index of the first total that reaches `needed`, or of the last one if none do.
+fn reaching(totals: &[usize], needed: usize) -> usize {
+    totals
+        .partition_point(|&total| total < needed)
+        .min(totals.len() - 1)
+}
+
+impl fmt::Display for CleanupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CleanupError::NotEnoughSpace { needed, available } => write!(
+                f,
+                "{needed} bytes need deleting but only {available} can be"
+            ),
+            CleanupError::TooManyStates => write!(f, "too many ways to combine directories"),
+        }
+    }
+}
+
+impl std::error::Error for CleanupError {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::build_file_system;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    fn path(path: &str) -> Path {
+        path.parse().unwrap()
+    }
+
+    /// A file system where each directory holds one file of the given size.
+    fn file_system(dirs: &[(&str, usize)]) -> FileSystem {
+        let mut fs = FileSystem::new();
+        for &(dir, size) in dirs {
+            let id = fs.create_dir_all(&path(dir)).unwrap();
+            fs.add_file(id, "file", size).unwrap();
+        }
+        fs
+    }
+
+    #[test]
+    fn example_matches_part2() {
+        let fs = build_file_system(EXAMPLE).unwrap();
+
+        assert_eq!(
+            CleanupPlanner::new().plan(&fs),
+            Ok(Cleanup {
+                directories: vec![path("/d")],
+                freed: 24_933_642,
+            })
+        );
+    }
+
+    #[test]
+    fn combines_smaller_directories() {
+        let fs = file_system(&[("/a", 5), ("/a/x", 3), ("/b", 3), ("/c", 11), ("/d", 1)]);
+        let planner = CleanupPlanner::new().capacity(26).target_free(10);
+
+        // 23 used, so 3 bytes are free and 7 more are needed. /a (8) and /c (11) would do on
+        // their own but these three free exactly 7.
+        assert_eq!(planner.space_needed(&fs), 7);
+        assert_eq!(
+            planner.plan(&fs),
+            Ok(Cleanup {
+                directories: vec![path("/a/x"), path("/b"), path("/d")],
+                freed: 7,
+            })
+        );
+    }
+
+    #[test]
+    fn nothing_to_do() {
+        let fs = file_system(&[("/a", 5)]);
+
+        assert_eq!(
+            CleanupPlanner::new()
+                .capacity(100)
+                .target_free(95)
+                .plan(&fs),
+            Ok(Cleanup {
+                directories: Vec::new(),
+                freed: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn protected_paths_are_kept() {
+        let fs = file_system(&[("/a", 5), ("/a/b", 5), ("/c", 6), ("/d", 20)]);
+        let planner = CleanupPlanner::new().capacity(40).target_free(15);
+
+        // 36 used, so 11 bytes are needed.
+        assert_eq!(
+            planner.plan(&fs).unwrap().directories,
+            [path("/a/b"), path("/c")]
+        );
+
+        let planner = planner.protect(path("/a/b"));
+        assert_eq!(planner.plan(&fs).unwrap().directories, [path("/d")]);
+
+        let planner = planner.protect(path("/d/file"));
+        assert_eq!(
+            planner.plan(&fs),
+            Err(CleanupError::NotEnoughSpace {
+                needed: 11,
+                available: 6,
+            })
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = TestRng::new(11);
+
+        for _ in 0..50 {
+            let mut fs = FileSystem::new();
+            let mut dirs = vec![fs.root()];
+            for idx in 0..10 {
+                let parent = dirs[rng.below(dirs.len())];
+                let dir = fs.create_dir(parent, &idx.to_string()).unwrap();
+                fs.add_file(dir, "file", rng.below(100)).unwrap();
+                dirs.push(dir);
+            }
+            let used = fs.total_size(fs.root());
+            let planner = CleanupPlanner::new()
+                .capacity(used)
+                .target_free(rng.below(used + 1));
+            let needed = planner.space_needed(&fs);
+
+            // Try every set of directories that aren't nested.
+            let candidates = &dirs[1..];
+            let mut best = None;
+            for set in 0..1_usize << candidates.len() {
+                let chosen: Vec<NodeId> = (0..candidates.len())
+                    .filter(|idx| set & 1 << idx != 0)
+                    .map(|idx| candidates[idx])
+                    .collect();
+                let nested = chosen.iter().any(|&a| {
+                    chosen
+                        .iter()
+                        .any(|&b| a != b && fs.path(a).starts_with(&fs.path(b)))
+                });
+                let freed: usize = chosen.iter().map(|&id| fs.total_size(id)).sum();
+                if !nested && freed >= needed {
+                    best = Some(best.map_or(freed, |best: usize| best.min(freed)));
+                }
+            }
+
+            match planner.plan(&fs) {
+                Ok(cleanup) => {
+                    assert_eq!(Some(cleanup.freed), best);
+                    let total: usize = cleanup
+                        .directories
+                        .iter()
+                        .map(|path| fs.total_size(fs.lookup(path).unwrap()))
+                        .sum();
+                    assert_eq!(total, cleanup.freed);
+                }
+                Err(err) => {
+                    assert!(matches!(err, CleanupError::NotEnoughSpace { .. }));
+                    assert_eq!(best, None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gives_up_on_huge_searches() {
+        let dirs: Vec<(String, usize)> = (0..20).map(|idx| (format!("/{idx}"), 1 << idx)).collect();
+        let dirs: Vec<(&str, usize)> = dirs
+            .iter()
+            .map(|(dir, size)| (dir.as_str(), *size))
+            .collect();
+        let fs = file_system(&dirs);
+
+        let planner = CleanupPlanner::new()
+            .capacity(1 << 20)
+            .target_free((1 << 19) + 1)
+            .max_states(1000);
+        assert_eq!(planner.plan(&fs), Err(CleanupError::TooManyStates));
+    }
+}
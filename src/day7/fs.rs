@@ -0,0 +1,611 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// An absolute path like `/a/e`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Path {
+    components: Vec<String>,
+}
+
+impl Path {
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// The directory names from the root down, not including the root.
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    /// The last component, or `None` for the root.
+    pub fn name(&self) -> Option<&str> {
+        self.components.last().map(String::as_str)
+    }
+
+    /// How many directories below the root this is.
+    pub fn depth(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn parent(&self) -> Option<Path> {
+        let (_, parent) = self.components.split_last()?;
+        Some(Path {
+            components: parent.to_vec(),
+        })
+    }
+
+    /// The path to `name` inside this directory.
+    pub fn join(&self, name: &str) -> Path {
+        let mut components = self.components.clone();
+        components.push(name.to_string());
+        Path { components }
+    }
+
+    /// Follows `target` from here like `cd` does. Absolute targets start again from the root, `..`
+    /// goes up a directory (staying put at the root) and `.` and empty components are skipped.
+    pub fn resolve(&self, target: &str) -> Path {
+        let mut components = if target.starts_with('/') {
+            Vec::new()
+        } else {
+            self.components.clone()
+        };
+
+        for component in target.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    components.pop();
+                }
+                name => components.push(name.to_string()),
+            }
+        }
+
+        Path { components }
+    }
+
+    /// Whether this is `other` or somewhere inside it.
+    pub fn starts_with(&self, other: &Path) -> bool {
+        self.components.starts_with(&other.components)
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_root() {
+            return write!(f, "/");
+        }
+        for component in &self.components {
+            write!(f, "/{component}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = std::convert::Infallible;
+
+    /// Relative paths are treated as relative to the root.
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        Ok(Path::root().resolve(path))
+    }
+}
+
+/// A file or directory in a `FileSystem`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Clone, Debug)]
+struct Node {
+    name: String,
+    parent: Option<NodeId>,
+    // For directories, the total size of everything inside. Kept up to date as files are added.
+    size: usize,
+    kind: Kind,
+    metadata: Option<Metadata>,
+}
+
+#[derive(Clone, Debug)]
+enum Kind {
+    Directory(BTreeMap<String, NodeId>),
+    File,
+}
+
+/// What `ls -l` shows about a file or directory besides its size, as it was written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    /// Like `drwxr-xr-x`.
+    pub permissions: String,
+    /// Like `Jan  1 12:00` or `Jan  1  2022`.
+    pub modified: String,
+}
+
+/// A tree of directories and files.
+///
+/// Nodes live in one list and refer to each other by index, so nothing about the tree (building
+/// it, walking it or dropping it) needs recursion however deep it gets.
+#[derive(Clone, Debug)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FsError {
+    /// Something on the way to a directory is a file.
+    NotADirectory(Path),
+    /// Tried to make a file where there's already a directory.
+    IsADirectory(Path),
+    /// Tried to move something onto a directory that's already there.
+    AlreadyExists(Path),
+    /// Tried to move a directory inside itself.
+    InsideItself(Path),
+    /// The root can't be moved or removed.
+    IsRoot,
+}
+
+impl FileSystem {
+    /// A file system with nothing but the root directory.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: String::new(),
+                parent: None,
+                size: 0,
+                kind: Kind::Directory(BTreeMap::new()),
+                metadata: None,
+            }],
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// The name of the file or directory. The root's name is empty.
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.nodes[id.0].name
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id.0].kind, Kind::Directory(_))
+    }
+
+    /// The size of a file, or `None` for directories.
+    pub fn file_size(&self, id: NodeId) -> Option<usize> {
+        match self.nodes[id.0].kind {
+            Kind::File => Some(self.nodes[id.0].size),
+            Kind::Directory(_) => None,
+        }
+    }
+
+    /// The size of a file, or of everything inside a directory.
+    pub fn total_size(&self, id: NodeId) -> usize {
+        self.nodes[id.0].size
+    }
+
+    /// The permissions and modification time, if an `ls -l` has shown them.
+    pub fn metadata(&self, id: NodeId) -> Option<&Metadata> {
+        self.nodes[id.0].metadata.as_ref()
+    }
+
+    pub fn set_metadata(&mut self, id: NodeId, metadata: Metadata) {
+        self.nodes[id.0].metadata = Some(metadata);
+    }
+
+    /// Everything directly inside a directory, sorted by name. Files don't have any children.
+    pub fn children(&self, id: NodeId) -> impl DoubleEndedIterator<Item = NodeId> + '_ {
+        let children = match &self.nodes[id.0].kind {
+            Kind::Directory(children) => Some(children.values().copied()),
+            Kind::File => None,
+        };
+        children.into_iter().flatten()
+    }
+
+    pub fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        match &self.nodes[id.0].kind {
+            Kind::Directory(children) => children.get(name).copied(),
+            Kind::File => None,
+        }
+    }
+
+    pub fn lookup(&self, path: &Path) -> Option<NodeId> {
+        path.components()
+            .iter()
+            .try_fold(self.root(), |id, name| self.child(id, name))
+    }
+
+    pub fn path(&self, id: NodeId) -> Path {
+        let mut components = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            components.push(self.name(current).to_string());
+            current = parent;
+        }
+        components.reverse();
+        Path { components }
+    }
+
+    /// Every directory from the root down, with how deep it is and its total size.
+    pub fn directories(&self, order: Order) -> Directories<'_> {
+        Directories {
+            fs: self,
+            order,
+            stack: vec![(self.root(), 0, false)],
+        }
+    }
+
+    /// Makes the directory `name` inside `parent`, unless it's already there.
+    pub fn create_dir(&mut self, parent: NodeId, name: &str) -> Result<NodeId, FsError> {
+        match self.child(parent, name) {
+            Some(child) if self.is_dir(child) => Ok(child),
+            Some(child) => Err(FsError::NotADirectory(self.path(child))),
+            None if self.is_dir(parent) => {
+                Ok(self.insert(parent, name, Kind::Directory(BTreeMap::new()), 0))
+            }
+            None => Err(FsError::NotADirectory(self.path(parent))),
+        }
+    }
+
+    /// Makes the directory at `path` along with any missing directories above it.
+    pub fn create_dir_all(&mut self, path: &Path) -> Result<NodeId, FsError> {
+        path.components()
+            .iter()
+            .try_fold(self.root(), |dir, name| self.create_dir(dir, name))
+    }
+
+    /// Adds a file to the directory `dir`, or updates its size if it's already there.
+    pub fn add_file(&mut self, dir: NodeId, name: &str, size: usize) -> Result<NodeId, FsError> {
+        if !self.is_dir(dir) {
+            return Err(FsError::NotADirectory(self.path(dir)));
+        }
+
+        match self.child(dir, name) {
+            Some(existing) if self.is_dir(existing) => {
+                Err(FsError::IsADirectory(self.path(existing)))
+            }
+            Some(existing) => {
+                let old_size = self.nodes[existing.0].size;
+                self.resize(existing, old_size, size);
+                Ok(existing)
+            }
+            None => Ok(self.insert(dir, name, Kind::File, size)),
+        }
+    }
+
+    /// Takes a file or directory and everything inside it out of the tree. Its id shouldn't be
+    /// used afterwards.
+    pub fn remove(&mut self, id: NodeId) -> Result<(), FsError> {
+        self.detach(id)?;
+        Ok(())
+    }
+
+    /// Moves a file or directory into the directory `parent` as `name`. A file already called
+    /// `name` is replaced like `mv` does, but a directory isn't.
+    pub fn move_to(&mut self, id: NodeId, parent: NodeId, name: &str) -> Result<(), FsError> {
+        if !self.is_dir(parent) {
+            return Err(FsError::NotADirectory(self.path(parent)));
+        }
+        let mut current = Some(parent);
+        while let Some(ancestor) = current {
+            if ancestor == id {
+                return Err(FsError::InsideItself(self.path(id)));
+            }
+            current = self.parent(ancestor);
+        }
+
+        match self.child(parent, name) {
+            Some(existing) if existing == id => return Ok(()),
+            Some(existing) if self.is_dir(existing) => {
+                return Err(FsError::AlreadyExists(self.path(existing)))
+            }
+            Some(_) if self.is_dir(id) => {
+                return Err(FsError::NotADirectory(self.path(parent).join(name)))
+            }
+            Some(existing) => self.remove(existing)?,
+            None => {}
+        }
+
+        let size = self.detach(id)?;
+        self.nodes[id.0].name = name.to_string();
+        self.attach(id, parent, size);
+        Ok(())
+    }
+
+    fn insert(&mut self, parent: NodeId, name: &str, kind: Kind, size: usize) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: None,
+            size,
+            kind,
+            metadata: None,
+        });
+        self.attach(id, parent, size);
+        id
+    }
+
+    /// Puts `id` in `parent` under its name and adds its `size` to every directory above.
+    fn attach(&mut self, id: NodeId, parent: NodeId, size: usize) {
+        let name = self.nodes[id.0].name.clone();
+        match &mut self.nodes[parent.0].kind {
+            Kind::Directory(children) => children.insert(name, id),
+            Kind::File => unreachable!("Only directories get children"),
+        };
+        self.nodes[id.0].parent = Some(parent);
+        self.resize(parent, 0, size);
+    }
+
+    /// Takes `id` out of its directory and returns its size, which no longer counts towards the
+    /// directories above.
+    fn detach(&mut self, id: NodeId) -> Result<usize, FsError> {
+        let parent = self.parent(id).ok_or(FsError::IsRoot)?;
+        let name = self.nodes[id.0].name.clone();
+        match &mut self.nodes[parent.0].kind {
+            Kind::Directory(children) => children.remove(&name),
+            Kind::File => unreachable!("Only directories get children"),
+        };
+        self.nodes[id.0].parent = None;
+
+        let size = self.nodes[id.0].size;
+        self.resize(parent, size, 0);
+        Ok(size)
+    }
+
+    /// Changes the size of `id` and every directory above it from `old` to `new`.
+    fn resize(&mut self, id: NodeId, old: usize, new: usize) {
+        // Skipping this matters for empty directories, which would otherwise walk up the whole
+        // tree every time one is made.
+        if old == new {
+            return;
+        }
+
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let node = &mut self.nodes[id.0];
+            node.size = node.size - old + new;
+            current = node.parent;
+        }
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which way round `FileSystem::directories` visits a directory and what's inside it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// Each directory comes before everything inside it.
+    PreOrder,
+    /// Each directory comes after everything inside it.
+    PostOrder,
+}
+
+/// The path to a node, only worked out when it's needed, so walking a very deep tree doesn't
+/// have to build every path on the way down.
+#[derive(Clone, Copy)]
+pub struct NodePath<'a> {
+    fs: &'a FileSystem,
+    id: NodeId,
+}
+
+impl NodePath<'_> {
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn to_path(&self) -> Path {
+        self.fs.path(self.id)
+    }
+}
+
+impl fmt::Display for NodePath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_path().fmt(f)
+    }
+}
+
+impl fmt::Debug for NodePath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Walks every directory with an explicit stack rather than recursion. Yields each directory's
+/// path, how far below the root it is and its total size.
+pub struct Directories<'a> {
+    fs: &'a FileSystem,
+    order: Order,
+    // Directories still to visit, their depth and (for post-order) whether everything inside has
+    // already been pushed.
+    stack: Vec<(NodeId, usize, bool)>,
+}
+
+impl<'a> Iterator for Directories<'a> {
+    type Item = (NodePath<'a>, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (id, depth, expanded) = self.stack.pop()?;
+            let item = (NodePath { fs: self.fs, id }, depth, self.fs.total_size(id));
+
+            if self.order == Order::PostOrder {
+                if expanded {
+                    return Some(item);
+                }
+                self.stack.push((id, depth, true));
+            }
+
+            // Backwards so the first directory is popped first.
+            let fs = self.fs;
+            self.stack.extend(
+                fs.children(id)
+                    .rev()
+                    .filter(|&child| fs.is_dir(child))
+                    .map(|child| (child, depth + 1, false)),
+            );
+
+            if self.order == Order::PreOrder {
+                return Some(item);
+            }
+        }
+    }
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FsError::NotADirectory(path) => write!(f, "{path} is not a directory"),
+            FsError::IsADirectory(path) => write!(f, "{path} is a directory"),
+            FsError::AlreadyExists(path) => write!(f, "{path} already exists"),
+            FsError::InsideItself(path) => write!(f, "{path} can't be moved inside itself"),
+            FsError::IsRoot => write!(f, "the root can't be moved or removed"),
+        }
+    }
+}
+
+impl std::error::Error for FsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Path {
+        path.parse().unwrap()
+    }
+
+    #[test]
+    fn resolving_paths() {
+        let cwd = path("/a/b");
+
+        assert_eq!(cwd.resolve("c").to_string(), "/a/b/c");
+        assert_eq!(cwd.resolve("..").to_string(), "/a");
+        assert_eq!(cwd.resolve("../../..").to_string(), "/");
+        assert_eq!(cwd.resolve("/x/y").to_string(), "/x/y");
+        assert_eq!(cwd.resolve("./c//d/").to_string(), "/a/b/c/d");
+        assert_eq!(cwd.resolve("/").to_string(), "/");
+
+        assert_eq!(cwd.parent(), Some(path("/a")));
+        assert_eq!(Path::root().parent(), None);
+        assert_eq!(cwd.name(), Some("b"));
+        assert!(cwd.starts_with(&path("/a")));
+        assert!(!path("/ab").starts_with(&path("/a")));
+    }
+
+    #[test]
+    fn building_a_tree() {
+        let mut fs = FileSystem::new();
+        let e = fs.create_dir_all(&path("/a/e")).unwrap();
+        fs.add_file(e, "i", 584).unwrap();
+        let a = fs.lookup(&path("/a")).unwrap();
+        fs.add_file(a, "f", 29116).unwrap();
+
+        assert_eq!(fs.total_size(fs.root()), 584 + 29116);
+        assert_eq!(fs.total_size(e), 584);
+
+        let i = fs.lookup(&path("/a/e/i")).unwrap();
+        assert_eq!(fs.file_size(i), Some(584));
+        assert_eq!(fs.path(i), path("/a/e/i"));
+        assert_eq!(fs.lookup(&path("/a/x")), None);
+
+        let names: Vec<&str> = fs.children(a).map(|id| fs.name(id)).collect();
+        assert_eq!(names, ["e", "f"]);
+
+        // Listing the same file again doesn't count it twice, but changes are picked up.
+        fs.add_file(e, "i", 584).unwrap();
+        assert_eq!(fs.total_size(fs.root()), 584 + 29116);
+        fs.add_file(e, "i", 100).unwrap();
+        assert_eq!(fs.total_size(a), 100 + 29116);
+    }
+
+    #[test]
+    fn walking_directories() {
+        let mut fs = FileSystem::new();
+        for dir in ["/a/b", "/a/c", "/d"] {
+            let id = fs.create_dir_all(&path(dir)).unwrap();
+            fs.add_file(id, "f", 1).unwrap();
+        }
+
+        let walk = |order| {
+            fs.directories(order)
+                .map(|(path, depth, size)| (path.to_string(), depth, size))
+                .collect::<Vec<_>>()
+        };
+        let entry = |path: &str, depth, size| (path.to_string(), depth, size);
+
+        assert_eq!(
+            walk(Order::PreOrder),
+            [
+                entry("/", 0, 3),
+                entry("/a", 1, 2),
+                entry("/a/b", 2, 1),
+                entry("/a/c", 2, 1),
+                entry("/d", 1, 1),
+            ]
+        );
+        assert_eq!(
+            walk(Order::PostOrder),
+            [
+                entry("/a/b", 2, 1),
+                entry("/a/c", 2, 1),
+                entry("/a", 1, 2),
+                entry("/d", 1, 1),
+                entry("/", 0, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn very_deep_trees() {
+        let depth = 200_000;
+        let mut fs = FileSystem::new();
+        let mut dir = fs.root();
+        for _ in 0..depth {
+            dir = fs.create_dir(dir, "d").unwrap();
+        }
+        fs.add_file(dir, "f", 7).unwrap();
+
+        let mut post_order = fs.directories(Order::PostOrder);
+        let (deepest, deepest_depth, size) = post_order.next().unwrap();
+        assert_eq!(deepest.id(), dir);
+        assert_eq!((deepest_depth, size), (depth, 7));
+        assert_eq!(post_order.count(), depth);
+
+        assert!(fs
+            .directories(Order::PreOrder)
+            .all(|(_, _, size)| size == 7));
+    }
+
+    #[test]
+    fn files_and_directories_dont_mix() {
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        fs.add_file(root, "a", 1).unwrap();
+        let b = fs.create_dir_all(&path("/b")).unwrap();
+
+        assert_eq!(
+            fs.create_dir_all(&path("/a/c")),
+            Err(FsError::NotADirectory(path("/a")))
+        );
+        assert_eq!(
+            fs.add_file(root, "b", 1),
+            Err(FsError::IsADirectory(path("/b")))
+        );
+        let a = fs.lookup(&path("/a")).unwrap();
+        assert_eq!(
+            fs.add_file(a, "x", 1),
+            Err(FsError::NotADirectory(path("/a")))
+        );
+        assert!(fs.is_dir(b));
+    }
+}
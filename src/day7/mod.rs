@@ -1,93 +1,40 @@
-use std::collections::{HashMap, HashSet};
+use cleanup::CleanupPlanner;
+use fs::{FileSystem, Order};
+use shell::{Shell, TranscriptError};
+
+pub mod cleanup;
+pub mod fs;
+pub mod report;
+pub mod shell;
 
 pub fn part1(input: &str) -> usize {
-    let fs = build_file_system(input);
+    let fs = build_file_system(input).unwrap_or_else(|err| panic!("Invalid transcript: {err}"));
 
-    dir_sizes(&fs)
-        .into_iter()
+    fs.directories(Order::PreOrder)
+        .map(|(_, _, size)| size)
         .filter(|&size| size <= 100_000)
         .sum()
 }
 
-// TODO write cool, stack based recursive iterator
-fn dir_sizes(fs: &FileSystem) -> Vec<usize> {
-    fn helper(fs: &FileSystem, sizes: &mut Vec<usize>) {
-        for directory in fs.values() {
-            sizes.push(directory.size);
-            helper(&directory.contents, sizes);
-        }
+/// Replays the terminal output in `input` to work out what's on the disk.
+pub fn build_file_system(input: &str) -> Result<FileSystem, TranscriptError> {
+    let mut shell = Shell::new();
+    for (idx, line) in input.lines().enumerate() {
+        shell.run(line).map_err(|kind| TranscriptError {
+            line: idx + 1,
+            kind,
+        })?;
     }
-
-    let mut sizes = Vec::new();
-    helper(fs, &mut sizes);
-    sizes
-}
-
-type FileSystem<'a> = HashMap<&'a str, Directory<'a>>;
-
-#[derive(Debug, Default)]
-struct Directory<'a> {
-    size: usize,
-    contents: FileSystem<'a>,
-}
-
-fn build_file_system(input: &str) -> FileSystem {
-    let mut path = Vec::new();
-    let mut fs = FileSystem::new();
-    let mut seen_paths = HashSet::new();
-    let mut skipping_ls = false;
-    for line in input.lines() {
-        if let Some(cd_target) = line.strip_prefix("$ cd ") {
-            skipping_ls = false;
-            match cd_target {
-                "/" => path.clear(),
-                ".." => {
-                    path.pop();
-                }
-                dir => path.push(dir),
-            }
-        } else if line.starts_with('$') {
-            // If we're `ls`ing the same path again, set a flag to skip all the file contents so we
-            // don't mess up any sizes.
-            //
-            // NOTE: In my input, this isn't needed at all. But it seems like a good idea.
-            let current_path = path.join("");
-            skipping_ls = !seen_paths.insert(current_path);
-        } else {
-            if skipping_ls {
-                continue;
-            }
-
-            let size: usize = match line.split(' ').next() {
-                // We can ignore directories listed here. We'll presumably `cd` into them later.
-                Some("dir") => continue,
-                Some(size) => size.parse().expect("File size should be valid"),
-                _ => panic!("Invalid result from `ls` command"),
-            };
-
-            let mut entry = fs.entry("/").or_default();
-            for dir in &path {
-                entry.size += size;
-                entry = entry.contents.entry(dir).or_default();
-            }
-            entry.size += size;
-        }
-    }
-
-    fs
+    Ok(shell.into_file_system())
 }
 
 pub fn part2(input: &str) -> usize {
-    let fs = build_file_system(input);
+    let fs = build_file_system(input).unwrap_or_else(|err| panic!("Invalid transcript: {err}"));
 
-    let disk_space = 70_000_000;
-    let total_needed = 30_000_000;
-    let total_used = fs.get("/").map_or(0, |dir| dir.size);
-    let total_free = disk_space - total_used;
-    let needed = total_needed - total_free;
+    let needed = CleanupPlanner::new().space_needed(&fs);
 
-    dir_sizes(&fs)
-        .into_iter()
+    fs.directories(Order::PreOrder)
+        .map(|(_, _, size)| size)
         .filter(|&size| size >= needed)
         .min()
         .expect("File system empty")
@@ -97,7 +44,7 @@ pub fn part2(input: &str) -> usize {
 mod tests {
     use super::*;
 
-    const EXAMPLE: &str = "$ cd /\n\
+    pub(super) const EXAMPLE: &str = "$ cd /\n\
                            $ ls\n\
                            dir a\n\
                            14848514 b.txt\n\
@@ -142,12 +89,73 @@ mod tests {
             assert_eq!(part1(file), 1);
         }
 
+        #[test]
+        fn similar_paths_dont_collide() {
+            // `/a/bc` and `/ab/c` used to both be tracked as "abc", so the second `ls` was
+            // skipped as a repeat.
+            let file = "$ cd /\n\
+                        $ ls\n\
+                        dir a\n\
+                        dir ab\n\
+                        $ cd a/bc\n\
+                        $ ls\n\
+                        10 x\n\
+                        $ cd /ab/c\n\
+                        $ ls\n\
+                        20 y";
+
+            // a = bc = 10, ab = c = 20, / = 30
+            assert_eq!(part1(file), 90);
+        }
+
+        #[test]
+        fn very_deep_transcript() {
+            // Deep enough that walking the tree recursively would overflow the stack.
+            let depth = 100_000;
+            let mut file = String::from("$ cd /\n");
+            for _ in 0..depth {
+                file.push_str("$ ls\ndir d\n$ cd d\n");
+            }
+            file.push_str("$ ls\n1 f\n");
+
+            // Every directory, including the root, holds the one file.
+            assert_eq!(part1(&file), depth + 1);
+        }
+
         #[test]
         fn my_input() {
             assert_eq!(part1(INPUT), 1_086_293);
         }
     }
 
+    mod file_system {
+        use super::*;
+
+        #[test]
+        fn keeps_files() {
+            let fs = build_file_system(EXAMPLE).unwrap();
+
+            let i = fs.lookup(&"/a/e/i".parse().unwrap()).unwrap();
+            assert_eq!(fs.file_size(i), Some(584));
+
+            let d = fs.lookup(&"/d".parse().unwrap()).unwrap();
+            let files: Vec<(&str, Option<usize>)> = fs
+                .children(d)
+                .map(|id| (fs.name(id), fs.file_size(id)))
+                .collect();
+            assert_eq!(
+                files,
+                [
+                    ("d.ext", Some(5626152)),
+                    ("d.log", Some(8033020)),
+                    ("j", Some(4060174)),
+                    ("k", Some(7214296)),
+                ]
+            );
+            assert_eq!(fs.total_size(d), 24933642);
+        }
+    }
+
     mod part2 {
         use super::*;
 
@@ -0,0 +1,231 @@
+//! Ways to look at a `FileSystem` rebuilt from a transcript.
+
+use std::fmt::Write;
+
+use super::fs::{FileSystem, NodeId, Order};
+
+/// A `tree` style listing with the size of every file and directory. Directories end in `/`.
+///
+/// ```text
+/// / (48381165)
+/// ├── a/ (94853)
+/// │   ├── e/ (584)
+/// │   │   └── i (584)
+/// ...
+/// ```
+pub fn tree(fs: &FileSystem) -> String {
+    let size = |id: NodeId| fs.total_size(id);
+
+    let mut out = String::new();
+    writeln!(out, "/ ({})", size(fs.root())).unwrap();
+
+    // (node, what goes in front of its line, whether it's the last thing in its directory)
+    let mut stack: Vec<(NodeId, String, bool)> = Vec::new();
+    push_children(fs, fs.root(), "", &mut stack);
+    while let Some((id, prefix, last)) = stack.pop() {
+        let branch = if last { "└── " } else { "├── " };
+        let slash = if fs.is_dir(id) { "/" } else { "" };
+        writeln!(out, "{prefix}{branch}{}{slash} ({})", fs.name(id), size(id)).unwrap();
+
+        let indent = if last { "    " } else { "│   " };
+        push_children(fs, id, &format!("{prefix}{indent}"), &mut stack);
+    }
+
+    out
+}
+
+fn push_children(
+    fs: &FileSystem,
+    id: NodeId,
+    prefix: &str,
+    stack: &mut Vec<(NodeId, String, bool)>,
+) {
+    // Backwards so the first child is popped first.
+    for (idx, child) in fs.children(id).rev().enumerate() {
+        stack.push((child, prefix.to_string(), idx == 0));
+    }
+}
+
+/// A `du -h` style listing of every directory no more than `max_depth` below the root (or every
+/// directory for `None`), biggest first.
+pub fn du(fs: &FileSystem, max_depth: Option<usize>) -> String {
+    let mut listing: Vec<(usize, String)> = fs
+        .directories(Order::PreOrder)
+        .filter(|(_, depth, _)| max_depth.is_none_or(|max_depth| *depth <= max_depth))
+        .map(|(path, _, size)| (size, path.to_string()))
+        .collect();
+    listing.sort_unstable_by(|(a_size, a_path), (b_size, b_path)| {
+        b_size.cmp(a_size).then_with(|| a_path.cmp(b_path))
+    });
+
+    let mut out = String::new();
+    for (size, path) in listing {
+        writeln!(out, "{}\t{path}", human_size(size)).unwrap();
+    }
+    out
+}
+
+/// Sizes in powers of 1024, rounded up like `du -h`: one decimal place below 10 and none above.
+fn human_size(size: usize) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut scaled = size as f64;
+    let mut unit = 0;
+    loop {
+        scaled /= 1024.0;
+        // Rounding up can take us to 1024 so check after rounding.
+        let rounded = if scaled < 10.0 {
+            (scaled * 10.0).ceil() / 10.0
+        } else {
+            scaled.ceil()
+        };
+        if rounded < 1024.0 || unit == UNITS.len() - 1 {
+            return if rounded < 10.0 {
+                format!("{rounded:.1}{}", UNITS[unit])
+            } else {
+                format!("{rounded:.0}{}", UNITS[unit])
+            };
+        }
+        unit += 1;
+    }
+}
+
+/// The whole hierarchy as JSON, e.g.
+/// `{"name":"/","type":"directory","size":3,"children":[{"name":"a","type":"file","size":3}]}`.
+/// Children are sorted by name.
+pub fn json(fs: &FileSystem) -> String {
+    let mut out = String::new();
+
+    // `None` closes the directory opened before it.
+    let mut stack = vec![Some(fs.root())];
+    let mut first_in_directory = true;
+    while let Some(next) = stack.pop() {
+        let Some(id) = next else {
+            out.push_str("]}");
+            first_in_directory = false;
+            continue;
+        };
+
+        if !first_in_directory {
+            out.push(',');
+        }
+        let name = if id == fs.root() { "/" } else { fs.name(id) };
+        out.push_str("{\"name\":");
+        push_json_string(&mut out, name);
+
+        match fs.file_size(id) {
+            Some(size) => {
+                write!(out, ",\"type\":\"file\",\"size\":{size}}}").unwrap();
+                first_in_directory = false;
+            }
+            None => {
+                write!(
+                    out,
+                    ",\"type\":\"directory\",\"size\":{},\"children\":[",
+                    fs.total_size(id)
+                )
+                .unwrap();
+                stack.push(None);
+                stack.extend(fs.children(id).rev().map(Some));
+                first_in_directory = true;
+            }
+        }
+    }
+
+    out
+}
+
+fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::build_file_system;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+
+    #[test]
+    fn tree_listing() {
+        let fs = build_file_system(EXAMPLE).unwrap();
+
+        assert_eq!(
+            tree(&fs),
+            "/ (48381165)\n\
+             ├── a/ (94853)\n\
+             │   ├── e/ (584)\n\
+             │   │   └── i (584)\n\
+             │   ├── f (29116)\n\
+             │   ├── g (2557)\n\
+             │   └── h.lst (62596)\n\
+             ├── b.txt (14848514)\n\
+             ├── c.dat (8504156)\n\
+             └── d/ (24933642)\n    \
+             ├── d.ext (5626152)\n    \
+             ├── d.log (8033020)\n    \
+             ├── j (4060174)\n    \
+             └── k (7214296)\n"
+        );
+    }
+
+    #[test]
+    fn du_listing() {
+        let fs = build_file_system(EXAMPLE).unwrap();
+
+        assert_eq!(du(&fs, Some(0)), "47M\t/\n");
+        assert_eq!(du(&fs, Some(1)), "47M\t/\n24M\t/d\n93K\t/a\n");
+        assert_eq!(du(&fs, None), "47M\t/\n24M\t/d\n93K\t/a\n584\t/a/e\n");
+    }
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(human_size(0), "0");
+        assert_eq!(human_size(1023), "1023");
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(1025), "1.1K");
+        assert_eq!(human_size(10 * 1024), "10K");
+        assert_eq!(human_size(1024 * 1024 - 1), "1.0M");
+        assert_eq!(human_size(5 * 1024 * 1024 * 1024), "5.0G");
+    }
+
+    #[test]
+    fn json_dump() {
+        let fs = build_file_system(
+            "$ cd /\n\
+             $ ls\n\
+             dir a\n\
+             3 \"quoted\"\n\
+             $ cd a\n\
+             $ ls\n\
+             dir empty\n\
+             1 x\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            json(&fs),
+            "{\"name\":\"/\",\"type\":\"directory\",\"size\":4,\"children\":[\
+             {\"name\":\"\\\"quoted\\\"\",\"type\":\"file\",\"size\":3},\
+             {\"name\":\"a\",\"type\":\"directory\",\"size\":1,\"children\":[\
+             {\"name\":\"empty\",\"type\":\"directory\",\"size\":0,\"children\":[]},\
+             {\"name\":\"x\",\"type\":\"file\",\"size\":1}\
+             ]}\
+             ]}"
+        );
+    }
+}
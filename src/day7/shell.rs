@@ -0,0 +1,513 @@
+//! Replaying the commands in a terminal transcript onto a `FileSystem`.
+//!
+//! The transcript only shows part of the disk, so `cd`, `mkdir`, `touch` and listings make any
+//! directories on the way that haven't been seen yet. `rm` and `mv` need what they act on to
+//! have been seen already. The transcript doesn't say where the home directory is, so `~` is the
+//! root.
+
+use std::fmt;
+
+use super::fs::{FileSystem, FsError, Metadata, NodeId};
+
+/// A shell that remembers the current directory and which directory is being listed.
+#[derive(Clone, Debug)]
+pub struct Shell {
+    fs: FileSystem,
+    cwd: NodeId,
+    listing: Option<Listing>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Listing {
+    dir: NodeId,
+    long: bool,
+}
+
+/// What went wrong on a line of a transcript. Line numbers start at 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptError {
+    pub line: usize,
+    pub kind: TranscriptErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptErrorKind {
+    UnknownCommand(String),
+    UnknownOption {
+        command: String,
+        option: String,
+    },
+    MissingOperand(String),
+    TooManyOperands(String),
+    /// A line that isn't a command and doesn't come after an `ls`.
+    UnexpectedOutput,
+    InvalidListing,
+    NoSuchFile(String),
+    FileSystem(FsError),
+}
+
+impl From<FsError> for TranscriptErrorKind {
+    fn from(err: FsError) -> Self {
+        TranscriptErrorKind::FileSystem(err)
+    }
+}
+
+impl Shell {
+    /// A shell at the root of an empty file system.
+    pub fn new() -> Self {
+        let fs = FileSystem::new();
+        Self {
+            cwd: fs.root(),
+            fs,
+            listing: None,
+        }
+    }
+
+    pub fn file_system(&self) -> &FileSystem {
+        &self.fs
+    }
+
+    pub fn into_file_system(self) -> FileSystem {
+        self.fs
+    }
+
+    pub fn cwd(&self) -> NodeId {
+        self.cwd
+    }
+
+    /// Runs one line of a transcript: either a command after `$ ` or a line of `ls` output.
+    /// Blank lines are skipped.
+    pub fn run(&mut self, line: &str) -> Result<(), TranscriptErrorKind> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let Some(command) = line.strip_prefix('$') else {
+            let listing = self.listing.ok_or(TranscriptErrorKind::UnexpectedOutput)?;
+            return if listing.long {
+                self.long_entry(listing.dir, line)
+            } else {
+                self.entry(listing.dir, line)
+            };
+        };
+        self.listing = None;
+
+        let mut words = command.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(());
+        };
+        let (options, operands): (Vec<&str>, Vec<&str>) =
+            words.partition(|word| word.starts_with('-') && word.len() > 1);
+        // The letters of every option, so `-rf` is the same as `-r -f`.
+        let flags: String = options.iter().map(|option| &option[1..]).collect();
+        let allow = |allowed: &str| match flags.chars().find(|flag| !allowed.contains(*flag)) {
+            Some(flag) => Err(TranscriptErrorKind::UnknownOption {
+                command: name.to_string(),
+                option: format!("-{flag}"),
+            }),
+            None => Ok(()),
+        };
+        let missing = || TranscriptErrorKind::MissingOperand(name.to_string());
+        let too_many = || TranscriptErrorKind::TooManyOperands(name.to_string());
+
+        match name {
+            "cd" => {
+                allow("")?;
+                self.cwd = match operands[..] {
+                    [] => self.fs.root(),
+                    [target] => self.make_dirs(target)?,
+                    _ => return Err(too_many()),
+                };
+            }
+            "ls" => {
+                allow("la")?;
+                let dir = match operands[..] {
+                    [] => self.cwd,
+                    [target] => self.make_dirs(target)?,
+                    _ => return Err(too_many()),
+                };
+                self.listing = Some(Listing {
+                    dir,
+                    long: flags.contains('l'),
+                });
+            }
+            "mkdir" => {
+                allow("p")?;
+                if operands.is_empty() {
+                    return Err(missing());
+                }
+                for target in operands {
+                    self.make_dirs(target)?;
+                }
+            }
+            "touch" => {
+                allow("")?;
+                if operands.is_empty() {
+                    return Err(missing());
+                }
+                for target in operands {
+                    let (dir, name) = split_last(target);
+                    let dir = self.make_dirs(dir)?;
+                    if self.fs.child(dir, name).is_none() {
+                        self.fs.add_file(dir, name, 0)?;
+                    }
+                }
+            }
+            "rm" => {
+                allow("rRf")?;
+                let recursive = flags.contains(['r', 'R']);
+                let force = flags.contains('f');
+                if operands.is_empty() && !force {
+                    return Err(missing());
+                }
+                for target in operands {
+                    match self.find(target) {
+                        Some(id) if self.fs.is_dir(id) && !recursive => {
+                            return Err(FsError::IsADirectory(self.fs.path(id)).into())
+                        }
+                        Some(id) => self.fs.remove(id)?,
+                        None if force => {}
+                        None => return Err(TranscriptErrorKind::NoSuchFile(target.to_string())),
+                    }
+                }
+            }
+            "mv" => {
+                allow("")?;
+                let (&destination, sources) = operands.split_last().ok_or_else(missing)?;
+                if sources.is_empty() {
+                    return Err(missing());
+                }
+                let sources = sources
+                    .iter()
+                    .map(|&source| {
+                        self.find(source)
+                            .ok_or_else(|| TranscriptErrorKind::NoSuchFile(source.to_string()))
+                    })
+                    .collect::<Result<Vec<NodeId>, _>>()?;
+
+                match self.find(destination) {
+                    // Into the directory, keeping their names.
+                    Some(dir) if self.fs.is_dir(dir) => {
+                        for source in sources {
+                            let name = self.fs.name(source).to_string();
+                            self.fs.move_to(source, dir, &name)?;
+                        }
+                    }
+                    Some(file) if sources.len() > 1 => {
+                        return Err(FsError::NotADirectory(self.fs.path(file)).into())
+                    }
+                    None if sources.len() > 1 => {
+                        return Err(TranscriptErrorKind::NoSuchFile(destination.to_string()))
+                    }
+                    // Renamed to the destination.
+                    _ => {
+                        let (dir, name) = split_last(destination);
+                        let dir = self.make_dirs(dir)?;
+                        self.fs.move_to(sources[0], dir, name)?;
+                    }
+                }
+            }
+            _ => return Err(TranscriptErrorKind::UnknownCommand(name.to_string())),
+        }
+
+        Ok(())
+    }
+
+    /// A line of plain `ls` output like `dir a` or `14848514 b.txt`.
+    fn entry(&mut self, dir: NodeId, line: &str) -> Result<(), TranscriptErrorKind> {
+        let (size, name) = line
+            .split_once(' ')
+            .ok_or(TranscriptErrorKind::InvalidListing)?;
+        if size == "dir" {
+            self.fs.create_dir(dir, name)?;
+        } else {
+            let size = size
+                .parse()
+                .map_err(|_| TranscriptErrorKind::InvalidListing)?;
+            self.fs.add_file(dir, name, size)?;
+        }
+        Ok(())
+    }
+
+    /// A line of `ls -l` output like `-rw-r--r-- 1 elf elf 14848514 Dec  7 09:12 b.txt`. The
+    /// size shown for directories is ignored, and so are `total` lines and `.` and `..`.
+    fn long_entry(&mut self, dir: NodeId, line: &str) -> Result<(), TranscriptErrorKind> {
+        if line.starts_with("total ") {
+            return Ok(());
+        }
+
+        // Permissions, links, owner, group, size and three for the date, then the name, which
+        // might have spaces in it.
+        let mut fields = Vec::with_capacity(8);
+        let mut rest = line;
+        while fields.len() < 8 {
+            let (field, after) = rest
+                .trim_start()
+                .split_once(' ')
+                .ok_or(TranscriptErrorKind::InvalidListing)?;
+            fields.push(field);
+            rest = after;
+        }
+        let name = rest.trim_start();
+        let permissions = fields[0];
+        if name.is_empty() || permissions.chars().count() < 10 {
+            return Err(TranscriptErrorKind::InvalidListing);
+        }
+        if name == "." || name == ".." {
+            return Ok(());
+        }
+
+        let id = match permissions.chars().next() {
+            Some('d') => self.fs.create_dir(dir, name)?,
+            Some('-') => {
+                let size = fields[4]
+                    .parse()
+                    .map_err(|_| TranscriptErrorKind::InvalidListing)?;
+                self.fs.add_file(dir, name, size)?
+            }
+            _ => return Err(TranscriptErrorKind::InvalidListing),
+        };
+        self.fs.set_metadata(
+            id,
+            Metadata {
+                permissions: permissions.to_string(),
+                modified: fields[5..].join(" "),
+            },
+        );
+        Ok(())
+    }
+
+    /// Where `target` starts from, and what's left of it to follow.
+    fn start<'t>(&self, target: &'t str) -> (NodeId, &'t str) {
+        if let Some(rest) = target.strip_prefix('~') {
+            if rest.is_empty() || rest.starts_with('/') {
+                return (self.fs.root(), rest);
+            }
+        }
+        if target.starts_with('/') {
+            (self.fs.root(), target)
+        } else {
+            (self.cwd, target)
+        }
+    }
+
+    /// Follows `target` a directory at a time, making any that haven't been seen yet.
+    fn make_dirs(&mut self, target: &str) -> Result<NodeId, FsError> {
+        let (mut dir, rest) = self.start(target);
+        for component in rest.split('/') {
+            dir = match component {
+                "" | "." => dir,
+                ".." => self.fs.parent(dir).unwrap_or(dir),
+                name => self.fs.create_dir(dir, name)?,
+            };
+        }
+        Ok(dir)
+    }
+
+    /// Follows `target` to a file or directory that's already been seen.
+    fn find(&self, target: &str) -> Option<NodeId> {
+        let (start, rest) = self.start(target);
+        rest.split('/')
+            .try_fold(start, |id, component| match component {
+                "" | "." => Some(id),
+                ".." => Some(self.fs.parent(id).unwrap_or(id)),
+                name => self.fs.child(id, name),
+            })
+    }
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Splits `target` into its directory and the name at the end.
+fn split_last(target: &str) -> (&str, &str) {
+    let trimmed = target.trim_end_matches('/');
+    match trimmed.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((dir, name)) => (dir, name),
+        None => (".", trimmed),
+    }
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            TranscriptErrorKind::UnknownCommand(command) => {
+                write!(f, "unknown command `{command}`")
+            }
+            TranscriptErrorKind::UnknownOption { command, option } => {
+                write!(f, "`{command}` doesn't have an option `{option}`")
+            }
+            TranscriptErrorKind::MissingOperand(command) => {
+                write!(f, "`{command}` is missing an operand")
+            }
+            TranscriptErrorKind::TooManyOperands(command) => {
+                write!(f, "`{command}` has too many operands")
+            }
+            TranscriptErrorKind::UnexpectedOutput => {
+                write!(f, "output that doesn't come after `ls`")
+            }
+            TranscriptErrorKind::InvalidListing => write!(f, "invalid result from `ls`"),
+            TranscriptErrorKind::NoSuchFile(target) => {
+                write!(f, "{target} doesn't exist")
+            }
+            TranscriptErrorKind::FileSystem(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptError {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::build_file_system;
+    use super::super::fs::Path;
+    use super::*;
+
+    fn path(path: &str) -> Path {
+        path.parse().unwrap()
+    }
+
+    fn size_of(fs: &FileSystem, target: &str) -> Option<usize> {
+        fs.lookup(&path(target)).map(|id| fs.total_size(id))
+    }
+
+    #[test]
+    fn long_listings() {
+        let fs = build_file_system(
+            "$ cd ~\n\
+             $ ls -la\n\
+             total 12\n\
+             drwxr-xr-x 3 elf elf 4096 Dec  7 09:12 .\n\
+             drwxr-xr-x 3 elf elf 4096 Dec  7 09:12 ..\n\
+             drwxr-x--- 2 elf elf 4096 Dec  6 18:40 a\n\
+             -rw-r--r-- 1 elf elf  120 Nov 30  2021 notes for later.txt\n\
+             $ ls -l /a\n\
+             -rwxr-xr-x 1 elf elf 30 Dec  7 09:00 run\n",
+        )
+        .unwrap();
+
+        assert_eq!(size_of(&fs, "/"), Some(150));
+        let notes = fs.lookup(&path("/notes for later.txt")).unwrap();
+        assert_eq!(fs.file_size(notes), Some(120));
+        assert_eq!(
+            fs.metadata(notes),
+            Some(&Metadata {
+                permissions: "-rw-r--r--".into(),
+                modified: "Nov 30 2021".into(),
+            })
+        );
+        let a = fs.lookup(&path("/a")).unwrap();
+        assert_eq!(fs.metadata(a).unwrap().permissions, "drwxr-x---");
+        assert_eq!(size_of(&fs, "/a/run"), Some(30));
+    }
+
+    #[test]
+    fn changing_files() {
+        let fs = build_file_system(
+            "$ mkdir -p a/b c\n\
+             $ cd a/b\n\
+             $ ls\n\
+             10 x\n\
+             20 y\n\
+             $ touch z ../new ~/c/empty\n\
+             $ mv x /c\n\
+             $ mv y ../../y2\n\
+             $ cd /\n\
+             $ rm -r a/b\n\
+             $ rm -f missing\n",
+        )
+        .unwrap();
+
+        assert_eq!(size_of(&fs, "/a/b"), None);
+        assert_eq!(size_of(&fs, "/a/new"), Some(0));
+        assert_eq!(size_of(&fs, "/c/x"), Some(10));
+        assert_eq!(size_of(&fs, "/c/empty"), Some(0));
+        assert_eq!(size_of(&fs, "/y2"), Some(20));
+        assert_eq!(size_of(&fs, "/"), Some(30));
+    }
+
+    #[test]
+    fn moving_directories() {
+        let fs = build_file_system(
+            "$ cd /a/b\n\
+             $ ls\n\
+             5 f\n\
+             $ cd /\n\
+             $ mkdir c\n\
+             $ mv a/b c\n\
+             $ mv c d\n",
+        )
+        .unwrap();
+
+        assert_eq!(size_of(&fs, "/a"), Some(0));
+        assert_eq!(size_of(&fs, "/d/b/f"), Some(5));
+        assert_eq!(size_of(&fs, "/d"), Some(5));
+        assert_eq!(size_of(&fs, "/c"), None);
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        let error = |transcript: &str| build_file_system(transcript).unwrap_err();
+        let at = |line, kind| TranscriptError { line, kind };
+
+        assert_eq!(
+            error("$ cd /\n$ ls\n1 a\n$ cat a"),
+            at(4, TranscriptErrorKind::UnknownCommand("cat".into()))
+        );
+        assert_eq!(
+            error("$ cd /\n1 a"),
+            at(2, TranscriptErrorKind::UnexpectedOutput)
+        );
+        assert_eq!(
+            error("$ ls\nnonsense"),
+            at(2, TranscriptErrorKind::InvalidListing)
+        );
+        assert_eq!(
+            error("$ ls -l\n-rw-r--r-- 1 elf elf 12 Dec  7 b.txt"),
+            at(2, TranscriptErrorKind::InvalidListing)
+        );
+        assert_eq!(
+            error("$ ls -R"),
+            at(
+                1,
+                TranscriptErrorKind::UnknownOption {
+                    command: "ls".into(),
+                    option: "-R".into(),
+                }
+            )
+        );
+        assert_eq!(
+            error("$ rm gone"),
+            at(1, TranscriptErrorKind::NoSuchFile("gone".into()))
+        );
+        assert_eq!(
+            error("$ mkdir a\n$ rm a"),
+            at(
+                2,
+                TranscriptErrorKind::FileSystem(FsError::IsADirectory(path("/a")))
+            )
+        );
+        assert_eq!(
+            error("$ mkdir a/b\n$ mv a a/b"),
+            at(
+                2,
+                TranscriptErrorKind::FileSystem(FsError::InsideItself(path("/a")))
+            )
+        );
+        assert_eq!(
+            error("$ touch a\n$ cd a"),
+            at(
+                2,
+                TranscriptErrorKind::FileSystem(FsError::NotADirectory(path("/a")))
+            )
+        );
+        assert_eq!(
+            error("$ mv"),
+            at(1, TranscriptErrorKind::MissingOperand("mv".into()))
+        );
+    }
+}
@@ -0,0 +1,540 @@
+use std::fmt;
+use std::thread;
+
+/// A grid of tree heights, stored a row at a time and again a column at a time.
+///
+/// Every question about the forest looks along rows and along columns. Keeping a transposed copy
+/// means both directions run over contiguous memory, and the column results are merged back into
+/// the row results a block at a time at the end.
+///
+/// Each row or column is worked on by itself, so big forests can be split into bands of rows
+/// and columns that are worked on by separate threads, see `Forest::threads`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Forest {
+    width: usize,
+    height: usize,
+    // Heights from 0 to 9, row by row.
+    rows: Vec<u8>,
+    // The same, column by column.
+    columns: Vec<u8>,
+    threads: usize,
+}
+
+/// A line of the input that isn't a row of the forest. Lines and columns start at 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForestError {
+    pub line: usize,
+    pub kind: ForestErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForestErrorKind {
+    /// The row isn't as long as the first one.
+    Ragged {
+        expected: usize,
+        found: usize,
+    },
+    NotADigit {
+        column: usize,
+        found: char,
+    },
+}
+
+impl Forest {
+    /// One line of digits per row, every row the same length.
+    pub fn parse(input: &str) -> Result<Self, ForestError> {
+        let mut rows = Vec::with_capacity(input.len());
+        let mut width = None;
+        let mut height = 0;
+
+        for (idx, line) in input.lines().enumerate() {
+            let error = |kind| ForestError {
+                line: idx + 1,
+                kind,
+            };
+            if let Some(bad) = line.bytes().position(|byte| !byte.is_ascii_digit()) {
+                // Only worth counting characters once something's wrong.
+                let column = line[..bad].chars().count() + 1;
+                let found = line[bad..]
+                    .chars()
+                    .next()
+                    .expect("There's a character here");
+                return Err(error(ForestErrorKind::NotADigit { column, found }));
+            }
+            let expected = *width.get_or_insert(line.len());
+            if line.len() != expected {
+                return Err(error(ForestErrorKind::Ragged {
+                    expected,
+                    found: line.len(),
+                }));
+            }
+
+            rows.extend(line.bytes().map(|byte| byte - b'0'));
+            height += 1;
+        }
+
+        let width = width.unwrap_or(0);
+        let columns = transpose(&rows, height, width);
+        Ok(Self {
+            width,
+            height,
+            rows,
+            columns,
+            threads: 1,
+        })
+    }
+
+    /// Splits the work between up to `threads` threads. Every row and column is still worked on
+    /// by one thread, so the answers are the same however many there are.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether each tree can be seen from outside the forest, row by row.
+    pub fn visible(&self) -> Vec<bool> {
+        let mut from_rows = vec![false; self.rows.len()];
+        in_bands(&mut from_rows, self.width, self.threads, |first, band| {
+            for (row, visible) in self
+                .rows_from(first)
+                .zip(band.chunks_mut(self.width.max(1)))
+            {
+                mark_visible(row, visible);
+            }
+        });
+
+        let mut from_columns = vec![false; self.columns.len()];
+        in_bands(
+            &mut from_columns,
+            self.height,
+            self.threads,
+            |first, band| {
+                for (column, visible) in self
+                    .columns_from(first)
+                    .zip(band.chunks_mut(self.height.max(1)))
+                {
+                    mark_visible(column, visible);
+                }
+            },
+        );
+
+        self.merge_columns(&mut from_rows, &from_columns, |a, b| a || b);
+        from_rows
+    }
+
+    pub fn count_visible(&self) -> usize {
+        self.visible()
+            .into_iter()
+            .filter(|&visible| visible)
+            .count()
+    }
+
+    /// How many trees each tree can see up, down, left and right multiplied together, row by
+    /// row.
+    pub fn scenic_scores(&self) -> Vec<u64> {
+        let mut from_rows = vec![1; self.rows.len()];
+        in_bands(&mut from_rows, self.width, self.threads, |first, band| {
+            let mut stack = Vec::new();
+            for (row, scores) in self
+                .rows_from(first)
+                .zip(band.chunks_mut(self.width.max(1)))
+            {
+                multiply_viewing_distances(row, &mut stack, scores);
+            }
+        });
+
+        let mut from_columns = vec![1; self.columns.len()];
+        in_bands(
+            &mut from_columns,
+            self.height,
+            self.threads,
+            |first, band| {
+                let mut stack = Vec::new();
+                for (column, scores) in self
+                    .columns_from(first)
+                    .zip(band.chunks_mut(self.height.max(1)))
+                {
+                    multiply_viewing_distances(column, &mut stack, scores);
+                }
+            },
+        );
+
+        self.merge_columns(&mut from_rows, &from_columns, |a, b| a * b);
+        from_rows
+    }
+
+    pub fn max_scenic_score(&self) -> u64 {
+        self.scenic_scores().into_iter().max().unwrap_or(0)
+    }
+
+    /// Which trees can be seen from outside and every tree's scenic score.
+    pub fn survey(&self) -> Survey {
+        Survey {
+            width: self.width,
+            height: self.height,
+            visible: self.visible(),
+            scores: self.scenic_scores(),
+        }
+    }
+
+    /// Every row from the `first`th down.
+    fn rows_from(&self, first: usize) -> std::slice::Chunks<'_, u8> {
+        self.rows[first * self.width..].chunks(self.width.max(1))
+    }
+
+    /// Every column from the `first`th across.
+    fn columns_from(&self, first: usize) -> std::slice::Chunks<'_, u8> {
+        self.columns[first * self.height..].chunks(self.height.max(1))
+    }
+
+    /// Merges results worked out column by column into the same results worked out row by row.
+    /// Works in square blocks so both the reads and the writes stay in cache.
+    fn merge_columns<T: Copy + Send + Sync>(
+        &self,
+        from_rows: &mut [T],
+        from_columns: &[T],
+        merge: impl Fn(T, T) -> T + Sync,
+    ) {
+        let (rows, cols) = (self.height, self.width);
+        in_bands(from_rows, cols, self.threads, |first_row, band| {
+            let band_rows = band.len() / cols.max(1);
+            for row_start in (0..band_rows).step_by(BLOCK) {
+                for col_start in (0..cols).step_by(BLOCK) {
+                    for row in row_start..(row_start + BLOCK).min(band_rows) {
+                        for col in col_start..(col_start + BLOCK).min(cols) {
+                            let value = &mut band[row * cols + col];
+                            *value = merge(*value, from_columns[col * rows + first_row + row]);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Everything `Forest` works out about each tree. Positions are `(row, column)` from the top
+/// left, and whole grids are row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Survey {
+    width: usize,
+    height: usize,
+    visible: Vec<bool>,
+    scores: Vec<u64>,
+}
+
+impl Survey {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether each tree can be seen from outside the forest.
+    pub fn visible(&self) -> &[bool] {
+        &self.visible
+    }
+
+    pub fn scores(&self) -> &[u64] {
+        &self.scores
+    }
+
+    pub fn visible_rows(&self) -> impl Iterator<Item = &[bool]> {
+        self.visible.chunks(self.width.max(1))
+    }
+
+    pub fn score_rows(&self) -> impl Iterator<Item = &[u64]> {
+        self.scores.chunks(self.width.max(1))
+    }
+
+    pub fn is_visible(&self, (row, col): (usize, usize)) -> bool {
+        self.visible[row * self.width + col]
+    }
+
+    pub fn score(&self, (row, col): (usize, usize)) -> u64 {
+        self.scores[row * self.width + col]
+    }
+
+    pub fn max_score(&self) -> u64 {
+        self.scores.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Every tree with the highest scenic score, top row first.
+    pub fn best_trees(&self) -> Vec<(usize, usize)> {
+        let max_score = self.max_score();
+        self.scores
+            .iter()
+            .enumerate()
+            .filter(|&(_, &score)| score == max_score)
+            .map(|(idx, _)| (idx / self.width, idx % self.width))
+            .collect()
+    }
+}
+
+/// Marks the trees in `line` that are taller than everything before them or everything after.
+fn mark_visible(line: &[u8], visible: &mut [bool]) {
+    let mut tallest = None;
+    for (&tree, visible) in line.iter().zip(visible.iter_mut()) {
+        if tallest < Some(tree) {
+            *visible = true;
+            tallest = Some(tree);
+        }
+    }
+
+    let mut tallest = None;
+    for (&tree, visible) in line.iter().zip(visible.iter_mut()).rev() {
+        if tallest < Some(tree) {
+            *visible = true;
+            tallest = Some(tree);
+        }
+    }
+}
+
+/// Multiplies each score by how many trees can be seen from that tree in both directions along
+/// `line`.
+///
+/// `stack` holds the trees whose view forwards isn't blocked yet, with their heights going down
+/// towards the top. Each new tree blocks the view of every tree on the stack that isn't taller,
+/// so those are popped and their forward distances worked out. The tree left on top (or the last
+/// one popped, if it's the same height) is what blocks the new tree's view backwards. Every tree
+/// is pushed and popped at most once, so the whole line takes linear time.
+fn multiply_viewing_distances(line: &[u8], stack: &mut Vec<(u8, usize)>, scores: &mut [u64]) {
+    stack.clear();
+    for (idx, &tree) in line.iter().enumerate() {
+        // Back to the edge unless something blocks it.
+        let mut backwards = idx;
+        while let Some(&(height, blocked)) = stack.last() {
+            if height > tree {
+                backwards = idx - blocked;
+                break;
+            }
+            stack.pop();
+            scores[blocked] *= (idx - blocked) as u64;
+            if height == tree {
+                backwards = idx - blocked;
+                break;
+            }
+        }
+        scores[idx] *= backwards as u64;
+        stack.push((tree, idx));
+    }
+
+    // Nothing blocks these, so they can see to the far edge.
+    let last = line.len().saturating_sub(1);
+    for &(_, idx) in stack.iter() {
+        scores[idx] *= (last - idx) as u64;
+    }
+}
+
+/// How many rows and columns `transpose` and `Forest::merge_columns` work on at once.
+const BLOCK: usize = 32;
+
+/// Turns `rows` rows of `cols` values into `cols` rows of `rows` values, a block at a time like
+/// `Forest::merge_columns`.
+fn transpose<T: Copy + Default>(values: &[T], rows: usize, cols: usize) -> Vec<T> {
+    let mut transposed = vec![T::default(); values.len()];
+    for row_start in (0..rows).step_by(BLOCK) {
+        for col_start in (0..cols).step_by(BLOCK) {
+            for row in row_start..(row_start + BLOCK).min(rows) {
+                for col in col_start..(col_start + BLOCK).min(cols) {
+                    transposed[col * rows + row] = values[row * cols + col];
+                }
+            }
+        }
+    }
+    transposed
+}
+
+/// Splits `out`, which is made of lines `line_len` long, into up to `threads` bands of whole
+/// lines and calls `work` with the index of each band's first line and the band, each on its own
+/// thread. Stays on this thread if there's only one band.
+fn in_bands<T: Send>(
+    out: &mut [T],
+    line_len: usize,
+    threads: usize,
+    work: impl Fn(usize, &mut [T]) + Sync,
+) {
+    let lines = out.len() / line_len.max(1);
+    let lines_per_band = lines.div_ceil(threads).max(1);
+    if lines <= lines_per_band {
+        work(0, out);
+        return;
+    }
+
+    thread::scope(|scope| {
+        for (idx, band) in out.chunks_mut(lines_per_band * line_len).enumerate() {
+            let work = &work;
+            scope.spawn(move || work(idx * lines_per_band, band));
+        }
+    });
+}
+
+impl fmt::Display for ForestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ForestErrorKind::Ragged { expected, found } => write!(
+                f,
+                "expected {expected} trees like the first row but found {found}"
+            ),
+            ForestErrorKind::NotADigit { column, found } => {
+                write!(
+                    f,
+                    "expected a height at column {column} but found {found:?}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ForestError {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::naive;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    #[test]
+    fn example() {
+        let forest = Forest::parse(EXAMPLE).unwrap();
+
+        assert_eq!((forest.width(), forest.height()), (5, 5));
+        assert_eq!(forest.count_visible(), 21);
+        assert_eq!(forest.max_scenic_score(), 8);
+        assert_eq!(forest.scenic_scores()[5..10], [0, 1, 4, 1, 0], "second row");
+    }
+
+    #[test]
+    fn surveying() {
+        let survey = Forest::parse(EXAMPLE).unwrap().survey();
+
+        assert_eq!(survey.best_trees(), [(3, 2)]);
+        assert_eq!(survey.score((3, 2)), 8);
+        assert_eq!(survey.score((1, 2)), 4);
+        assert!(survey.is_visible((1, 1)));
+        assert!(!survey.is_visible((1, 3)));
+        assert_eq!(
+            survey.visible().iter().filter(|&&visible| visible).count(),
+            21
+        );
+        assert_eq!(survey.score_rows().nth(1), Some(&[0, 1, 4, 1, 0][..]));
+
+        // Ties are all reported.
+        assert_eq!(
+            Forest::parse("000\n010\n000\n010\n000")
+                .unwrap()
+                .survey()
+                .best_trees(),
+            [(1, 1), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn transposing() {
+        let values: Vec<usize> = (0..6).collect();
+
+        assert_eq!(transpose(&values, 2, 3), [0, 3, 1, 4, 2, 5]);
+        assert_eq!(transpose(&transpose(&values, 2, 3), 3, 2), values);
+    }
+
+    #[test]
+    fn matches_naive() {
+        let mut rng = TestRng::new(8);
+
+        for (width, height) in [(1, 1), (1, 7), (9, 1), (40, 3), (37, 70)] {
+            let input: String = (0..height)
+                .map(|_| {
+                    let mut row: String = (0..width)
+                        .map(|_| char::from(b'0' + rng.below(10) as u8))
+                        .collect();
+                    row.push('\n');
+                    row
+                })
+                .collect();
+            let forest = Forest::parse(&input).unwrap();
+
+            assert_eq!(
+                forest.count_visible() as u64,
+                naive::count_visible(&input),
+                "{width}x{height}"
+            );
+            assert_eq!(
+                forest.max_scenic_score(),
+                naive::max_scenic_score(&input),
+                "{width}x{height}"
+            );
+        }
+    }
+
+    #[test]
+    fn threads_agree() {
+        let mut rng = TestRng::new(49);
+
+        for (width, height) in [(1, 1), (3, 1), (1, 5), (37, 70), (101, 9)] {
+            let input: String = (0..height)
+                .map(|_| {
+                    let mut row: String = (0..width)
+                        .map(|_| char::from(b'0' + rng.below(10) as u8))
+                        .collect();
+                    row.push('\n');
+                    row
+                })
+                .collect();
+            let forest = Forest::parse(&input).unwrap();
+            let survey = forest.survey();
+
+            for threads in [2, 3, 4, 7, 64, 1000] {
+                assert_eq!(
+                    forest.clone().threads(threads).survey(),
+                    survey,
+                    "{width}x{height} on {threads} threads"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_forests() {
+        let error = |input: &str| Forest::parse(input).unwrap_err();
+
+        assert_eq!(
+            error("123\n45\n678"),
+            ForestError {
+                line: 2,
+                kind: ForestErrorKind::Ragged {
+                    expected: 3,
+                    found: 2,
+                },
+            }
+        );
+        assert_eq!(
+            error("123\n4é6"),
+            ForestError {
+                line: 2,
+                kind: ForestErrorKind::NotADigit {
+                    column: 2,
+                    found: 'é',
+                },
+            }
+        );
+        assert_eq!(
+            error("12x").to_string(),
+            "line 1: expected a height at column 3 but found 'x'"
+        );
+
+        let empty = Forest::parse("").unwrap().threads(4);
+        assert_eq!(empty.count_visible(), 0);
+        assert_eq!(empty.max_scenic_score(), 0);
+    }
+}
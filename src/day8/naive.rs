@@ -0,0 +1,124 @@
+//! The first way of solving day 8: every direction is walked straight over the rows, and each
+//! tree looks outwards until its view is blocked. Kept to check `Forest` against and to benchmark
+//! it.
+
+pub fn count_visible(input: &str) -> u64 {
+    // Collect to a Vec so we can iterate in reverse later.
+    let heights: Vec<&[u8]> = input.lines().map(|line| line.as_bytes()).collect();
+
+    let num_rows = heights.len();
+    let num_cols = heights[0].len();
+
+    let mut visible = vec![vec![false; num_cols]; num_rows];
+    let mut count = 0;
+
+    for (height_row, visible_row) in heights.iter().zip(&mut visible) {
+        let from_left = height_row.iter().copied().zip(&mut *visible_row);
+        count += count_visible_from_ground(from_left);
+
+        let from_right = height_row.iter().copied().zip(&mut *visible_row).rev();
+        count += count_visible_from_ground(from_right);
+    }
+
+    // Columns are walked a row at a time, which is what `Forest` avoids.
+    for col_idx in 0..num_cols {
+        let from_top = heights
+            .iter()
+            .map(|row| row[col_idx])
+            .zip(visible.iter_mut().map(|row| &mut row[col_idx]));
+        count += count_visible_from_ground(from_top);
+
+        let from_bottom = heights
+            .iter()
+            .map(|row| row[col_idx])
+            .zip(visible.iter_mut().map(|row| &mut row[col_idx]))
+            .rev();
+        count += count_visible_from_ground(from_bottom);
+    }
+
+    count
+}
+
+fn count_visible_from_ground<'a, I>(trees_and_slots: I) -> u64
+where
+    I: IntoIterator<Item = (u8, &'a mut bool)>,
+{
+    // Not obviously nicer than a for loop but it's always fun to play with `scan`
+    trees_and_slots
+        .into_iter()
+        .scan(None, |tallest, (tree, visible_slot)| {
+            let is_visible = tallest.map_or(true, |t| tree > t);
+            if !is_visible {
+                return Some(false);
+            }
+            *tallest = Some(tree);
+            let updated = !(*visible_slot);
+            *visible_slot = true;
+            Some(updated)
+        })
+        .filter(|updated| *updated)
+        .count() as u64
+}
+
+pub fn max_scenic_score(input: &str) -> u64 {
+    // Collect to a Vec so we can iterate in reverse later.
+    let heights: Vec<&[u8]> = input.lines().map(|line| line.as_bytes()).collect();
+
+    let num_rows = heights.len();
+    let num_cols = heights[0].len();
+
+    let mut scores = vec![vec![1; num_cols]; num_rows];
+    let mut max_score = 0;
+
+    // Looks outwards from every tree, so this is O(n) per tree.
+    for (height_row, score_row) in heights.iter().zip(&mut scores) {
+        for col_idx in 0..height_row.len() {
+            let tree = height_row[col_idx];
+
+            // Update scenic score from the left
+            let prev_trees = (0..col_idx).rev().map(|row_idx| height_row[row_idx]);
+            let visible_trees = count_visible_from_tree(tree, prev_trees);
+            score_row[col_idx] *= visible_trees;
+
+            // Update scenic score from the right
+            let prev_trees = &height_row[col_idx + 1..];
+            let visible_trees = count_visible_from_tree(tree, prev_trees.iter().copied());
+            score_row[col_idx] *= visible_trees;
+        }
+    }
+
+    for col_idx in 0..num_cols {
+        for row_idx in 0..num_rows {
+            let tree = heights[row_idx][col_idx];
+
+            // Update scenic score from the top
+            let prev_trees = (0..row_idx).rev().map(|row_idx| heights[row_idx][col_idx]);
+            let visible_trees = count_visible_from_tree(tree, prev_trees);
+            scores[row_idx][col_idx] *= visible_trees;
+
+            // Update scenic score from the bottom
+            let prev_trees = (row_idx + 1..num_rows).map(|row_idx| heights[row_idx][col_idx]);
+            let visible_trees = count_visible_from_tree(tree, prev_trees);
+            scores[row_idx][col_idx] *= visible_trees;
+
+            max_score = max_score.max(scores[row_idx][col_idx]);
+        }
+    }
+
+    max_score
+}
+
+fn count_visible_from_tree<I>(tree: u8, prev_trees: I) -> u64
+where
+    I: IntoIterator<Item = u8>,
+{
+    let mut visible_trees = 0;
+    for prev_tree in prev_trees {
+        visible_trees += 1;
+        if tree <= prev_tree {
+            break;
+        }
+    }
+
+    visible_trees
+}
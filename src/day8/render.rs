@@ -0,0 +1,125 @@
+//! Pictures of a `Survey`, to see where the good tree house spots are.
+
+use std::io::{self, Write};
+
+use super::forest::Survey;
+
+/// Characters for scenic scores from 0 up to the best score in the forest.
+const RAMP: &[u8] = b" .:-=+*#%@";
+
+/// `#` for trees that can be seen from outside the forest and `.` for hidden ones, a line per
+/// row.
+pub fn visibility_ascii(survey: &Survey) -> String {
+    let mut out = String::with_capacity((survey.width() + 1) * survey.height());
+    for row in survey.visible_rows() {
+        out.extend(row.iter().map(|&visible| if visible { '#' } else { '.' }));
+        out.push('\n');
+    }
+    out
+}
+
+/// Each tree's scenic score as a character from `" .:-=+*#%@"`, scaled so only a score of zero is
+/// a space and the best trees are `@`.
+pub fn heatmap_ascii(survey: &Survey) -> String {
+    let max_score = survey.max_score();
+    let mut out = String::with_capacity((survey.width() + 1) * survey.height());
+    for row in survey.score_rows() {
+        out.extend(
+            row.iter()
+                .map(|&score| char::from(RAMP[scale(score, max_score, RAMP.len() - 1)])),
+        );
+        out.push('\n');
+    }
+    out
+}
+
+/// Writes the scenic scores as a binary PGM image, from black for zero to white for the best
+/// trees.
+pub fn write_pgm<W: Write>(survey: &Survey, mut out: W) -> io::Result<()> {
+    let max_score = survey.max_score();
+    write!(out, "P5\n{} {}\n255\n", survey.width(), survey.height())?;
+    let pixels: Vec<u8> = survey
+        .scores()
+        .iter()
+        .map(|&score| scale(score, max_score, 255) as u8)
+        .collect();
+    out.write_all(&pixels)
+}
+
+/// Writes the scenic scores as a binary PPM image, going from black through red and yellow to
+/// white as the score goes up. The best trees are cyan so they stand out.
+pub fn write_ppm<W: Write>(survey: &Survey, mut out: W) -> io::Result<()> {
+    let max_score = survey.max_score();
+    write!(out, "P6\n{} {}\n255\n", survey.width(), survey.height())?;
+    let mut pixels = Vec::with_capacity(3 * survey.scores().len());
+    for &score in survey.scores() {
+        if score == max_score && max_score > 0 {
+            pixels.extend([0, 255, 255]);
+            continue;
+        }
+        let heat = scale(score, max_score, 3 * 255);
+        pixels.extend([0, 255, 510].map(|start| heat.saturating_sub(start).min(255) as u8));
+    }
+    out.write_all(&pixels)
+}
+
+/// Scales `score` into `0..=levels`, rounding up so only a score of zero gets level 0.
+fn scale(score: u64, max_score: u64, levels: usize) -> usize {
+    if max_score == 0 {
+        return 0;
+    }
+    (score as u128 * levels as u128).div_ceil(max_score as u128) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::forest::Forest;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+
+    #[test]
+    fn ascii() {
+        let survey = Forest::parse(EXAMPLE).unwrap().survey();
+
+        assert_eq!(
+            visibility_ascii(&survey),
+            "#####\n\
+             ###.#\n\
+             ##.##\n\
+             #.#.#\n\
+             #####\n"
+        );
+        assert_eq!(
+            heatmap_ascii(&survey),
+            "     \n \
+             :+: \n \
+             #:- \n \
+             :@= \n     \n"
+        );
+    }
+
+    #[test]
+    fn images() {
+        let survey = Forest::parse(EXAMPLE).unwrap().survey();
+
+        let mut pgm = Vec::new();
+        write_pgm(&survey, &mut pgm).unwrap();
+        let header = b"P5\n5 5\n255\n";
+        assert_eq!(&pgm[..header.len()], header);
+        let pixels = &pgm[header.len()..];
+        assert_eq!(pixels.len(), 25);
+        // Scores of 1, 4 and 8 in the second row and the fourth.
+        assert_eq!(pixels[5..10], [0, 32, 128, 32, 0]);
+        assert_eq!(pixels[17], 255);
+
+        let mut ppm = Vec::new();
+        write_ppm(&survey, &mut ppm).unwrap();
+        let header = b"P6\n5 5\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 75);
+        assert_eq!(pixels[..3], [0, 0, 0]);
+        assert_eq!(pixels[3 * 7..3 * 8], [255, 128, 0], "a score of 4");
+        assert_eq!(pixels[3 * 17..3 * 18], [0, 255, 255], "the best tree");
+    }
+}
@@ -2,40 +2,40 @@ use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::fmt;
 
-struct Rope<const N: usize> {
-    segments: [Point; N],
+/// A rope with any number of knots, chosen when it's made.
+struct Rope {
+    knots: Vec<Point>,
 }
 
-impl<const N: usize> fmt::Debug for Rope<N> {
+impl fmt::Debug for Rope {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str("[")?;
-        for point in &self.segments {
+        for point in &self.knots {
             write!(f, "({},{}),", point.x, point.y)?; // screw trailing commas
         }
         f.write_str("]")
     }
 }
 
-// Explicit implementation because `N` isn't necessarily <= 32. See
-// https://doc.rust-lang.org/std/primitive.array.html.
-impl<const N: usize> Default for Rope<N> {
-    fn default() -> Self {
+impl Rope {
+    fn new(num_knots: usize) -> Self {
+        assert!(num_knots > 0, "A rope needs at least one knot");
+
         Self {
-            segments: [Point::default(); N],
+            knots: vec![Point::default(); num_knots],
         }
     }
-}
 
-impl<const N: usize> Rope<N> {
-    fn step(&mut self, direction: Direction) {
-        assert!(N > 0);
+    /// Moves the head one step and pulls the rest of the rope along. Returns how many knots
+    /// moved, which are always the first ones: once a knot stays put so does everything after
+    /// it.
+    fn step(&mut self, direction: Direction) -> usize {
+        self.knots[0].x += direction.x;
+        self.knots[0].y += direction.y;
 
-        self.segments[0].x += direction.x;
-        self.segments[0].y += direction.y;
-
-        for i in 1..N {
-            let prev_pos = self.segments[i - 1];
-            let cur_pos = &mut self.segments[i];
+        for i in 1..self.knots.len() {
+            let prev_pos = self.knots[i - 1];
+            let cur_pos = &mut self.knots[i];
 
             let dx = prev_pos.x - cur_pos.x;
             let dy = prev_pos.y - cur_pos.y;
@@ -44,7 +44,7 @@ impl<const N: usize> Rope<N> {
             let dy_mag = dy.abs();
 
             if dx_mag <= 1 && dy_mag <= 1 {
-                continue;
+                return i;
             }
 
             // TODO make branchless
@@ -63,12 +63,12 @@ impl<const N: usize> Rope<N> {
                 }
             }
         }
+
+        self.knots.len()
     }
 
     fn tail(&self) -> Point {
-        assert!(N > 0);
-
-        self.segments[N - 1]
+        *self.knots.last().expect("Ropes always have a knot")
     }
 }
 
@@ -84,9 +84,13 @@ struct Direction {
 }
 
 pub fn part1(input: &str) -> u64 {
+    tail_positions(input, 2)
+}
+
+fn tail_positions(input: &str, num_knots: usize) -> u64 {
     let mut unique_positions = HashSet::new();
 
-    let mut rope = Rope::<2>::default();
+    let mut rope = Rope::new(num_knots);
 
     unique_positions.insert(rope.tail());
 
@@ -104,6 +108,34 @@ pub fn part1(input: &str) -> u64 {
     unique_positions.len() as u64
 }
 
+/// How many different cells each knot of a rope with `num_knots` knots visits, head first. A rope
+/// with no knots gives an empty table.
+pub fn visited_per_knot(input: &str, num_knots: usize) -> Vec<usize> {
+    if num_knots == 0 {
+        return Vec::new();
+    }
+    let mut rope = Rope::new(num_knots);
+    let mut visited: Vec<HashSet<Point>> = rope
+        .knots
+        .iter()
+        .map(|&start| HashSet::from([start]))
+        .collect();
+
+    for line in input.lines() {
+        let (direction, distance) = parse_movement(line);
+
+        for _ in 0..distance {
+            // Knots that didn't move can't have visited anywhere new.
+            let moved = rope.step(direction);
+            for (knot, visited) in rope.knots[..moved].iter().zip(&mut visited) {
+                visited.insert(*knot);
+            }
+        }
+    }
+
+    visited.iter().map(HashSet::len).collect()
+}
+
 fn parse_movement(line: &str) -> (Direction, i32) {
     let mut split = line.split(' ');
     let direction = split.next().expect("Should have direction");
@@ -122,22 +154,7 @@ fn parse_movement(line: &str) -> (Direction, i32) {
 }
 
 pub fn part2(input: &str) -> u64 {
-    let mut unique_positions = HashSet::new();
-
-    let mut rope = Rope::<10>::default();
-
-    unique_positions.insert(rope.tail());
-
-    for line in input.lines() {
-        let (direction, distance) = parse_movement(line);
-
-        for _ in 0..distance {
-            rope.step(direction);
-            unique_positions.insert(rope.tail());
-        }
-    }
-
-    unique_positions.len() as u64
+    tail_positions(input, 10)
 }
 
 #[cfg(test)]
@@ -195,4 +212,36 @@ mod tests {
             assert_eq!(part2(INPUT), 2376);
         }
     }
+
+    mod visited_per_knot {
+        use super::*;
+
+        #[test]
+        fn matches_the_parts() {
+            let visited = visited_per_knot(EXAMPLE1, 10);
+
+            assert_eq!(visited.len(), 10);
+            assert_eq!(visited[1], part1(EXAMPLE1) as usize);
+            assert_eq!(visited[9], part2(EXAMPLE1) as usize);
+        }
+
+        #[test]
+        fn any_length() {
+            let file = "R 5\n\
+                        U 8\n\
+                        L 8\n\
+                        D 3\n\
+                        R 17\n\
+                        D 10\n\
+                        L 25\n\
+                        U 20";
+
+            assert_eq!(visited_per_knot(file, 0), []);
+            assert_eq!(visited_per_knot(file, 1).len(), 1);
+            assert_eq!(visited_per_knot(file, 10)[9], 36);
+            let long = visited_per_knot(file, 40);
+            assert_eq!(long[..10], visited_per_knot(file, 10)[..]);
+            assert_eq!(long[39], 1, "the tail never leaves the start");
+        }
+    }
 }
@@ -0,0 +1,377 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Integer types that can be the endpoints of an `IntervalSet`.
+pub trait Endpoint: Copy + Ord + fmt::Debug {
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_succ(self) -> Option<Self>;
+
+    fn checked_pred(self) -> Option<Self>;
+
+    /// The number of values in `start..=end`, saturating at `u64::MAX`.
+    fn count(start: Self, end: Self) -> u64;
+}
+
+macro_rules! impl_endpoint {
+    ($($t:ty),*) => {
+        $(
+            impl Endpoint for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn checked_succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn checked_pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn count(start: Self, end: Self) -> u64 {
+                    let count = end as i128 - start as i128 + 1;
+                    count.clamp(0, u64::MAX as i128) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_endpoint!(i32, i64, u32, u64, usize);
+
+/// A set of integers stored as sorted, disjoint, inclusive ranges.
+///
+/// Ranges that touch (e.g. `1..=2` and `3..=4`) are merged so every range in the set is as big as
+/// it can be.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    // Sorted by start. Each range ends at least 2 before the next range starts.
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        // Everything from `first` up to (not including) `last` overlaps or touches the new range.
+        let first = self
+            .ranges
+            .partition_point(|(_, e)| e.checked_succ().is_some_and(|after| after < start));
+        let last = self
+            .ranges
+            .partition_point(|(s, _)| match end.checked_succ() {
+                Some(after) => *s <= after,
+                None => true,
+            });
+
+        let merged = if first < last {
+            (
+                start.min(self.ranges[first].0),
+                end.max(self.ranges[last - 1].1),
+            )
+        } else {
+            (start, end)
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        *self = self.difference(&Self::from(range));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges: Vec<_> = self.ranges.iter().chain(&other.ranges).copied().collect();
+        ranges.sort_unstable();
+        Self::from_sorted(ranges)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let start = a.0.max(b.0);
+            let end = a.1.min(b.1);
+            if start <= end {
+                ranges.push((start, end));
+            }
+
+            // Whichever range ends first can't overlap anything else.
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement(T::MIN..=T::MAX))
+    }
+
+    /// Everything in `bounds` that isn't in the set.
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        let (start, end) = bounds.into_inner();
+        let mut ranges = Vec::new();
+        if start > end {
+            return Self { ranges };
+        }
+
+        // `None` once we've passed the end of `T`.
+        let mut next_start = Some(start);
+        for &(s, e) in &self.ranges {
+            let Some(gap_start) = next_start else {
+                break;
+            };
+            if s > end {
+                break;
+            }
+            if let Some(gap_end) = s.checked_pred() {
+                if gap_start <= gap_end {
+                    ranges.push((gap_start, gap_end.min(end)));
+                }
+            }
+            next_start = e.checked_succ().map(|after| after.max(gap_start));
+        }
+
+        if let Some(gap_start) = next_start {
+            if gap_start <= end {
+                ranges.push((gap_start, end));
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.ranges.partition_point(|(_, e)| *e < value);
+        self.ranges.get(idx).is_some_and(|(s, _)| *s <= value)
+    }
+
+    /// Whether every value in `range` is in the set.
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+
+        // Ranges are as big as they can be so `range` has to fit inside just one of them.
+        let idx = self.ranges.partition_point(|(_, e)| e < range.start());
+        self.ranges
+            .get(idx)
+            .is_some_and(|(s, e)| s <= range.start() && range.end() <= e)
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other
+            .ranges
+            .iter()
+            .all(|(s, e)| self.contains_range(&(*s..=*e)))
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set, saturating at `u64::MAX`.
+    pub fn len(&self) -> u64 {
+        self.ranges
+            .iter()
+            .fold(0, |len: u64, (s, e)| len.saturating_add(T::count(*s, *e)))
+    }
+
+    /// The ranges in the set, smallest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|(s, e)| *s..=*e)
+    }
+
+    /// The ranges between the ranges of the set, smallest first.
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.windows(2).map(|pair| {
+            // Ranges never touch so these can't overflow.
+            let start = pair[0].1.checked_succ().expect("Ranges are separated");
+            let end = pair[1].0.checked_pred().expect("Ranges are separated");
+            start..=end
+        })
+    }
+
+    fn from_sorted(sorted: Vec<(T, T)>) -> Self {
+        let mut ranges: Vec<(T, T)> = Vec::with_capacity(sorted.len());
+        for (start, end) in sorted {
+            if start > end {
+                continue;
+            }
+            match ranges.last_mut() {
+                // Note that ranges that are _touching but not overlapping_ should be combined.
+                Some(last) if last.1.checked_succ().is_none_or(|after| start <= after) => {
+                    last.1 = last.1.max(end);
+                }
+                _ => ranges.push((start, end)),
+            }
+        }
+        Self { ranges }
+    }
+}
+
+impl<T: Endpoint> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Endpoint> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut ranges: Vec<_> = iter.into_iter().map(RangeInclusive::into_inner).collect();
+        ranges.sort_unstable();
+        Self::from_sorted(ranges)
+    }
+}
+
+impl<T: Endpoint> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, iter: I) {
+        *self = self.union(&iter.into_iter().collect());
+    }
+}
+
+impl<T: Endpoint> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[RangeInclusive<i64>]) -> IntervalSet<i64> {
+        ranges.iter().cloned().collect()
+    }
+
+    fn ranges(set: &IntervalSet<i64>) -> Vec<RangeInclusive<i64>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn insert_merges_overlapping_and_touching() {
+        let mut s = IntervalSet::new();
+        s.insert(5..=7);
+        s.insert(1..=2);
+        s.insert(10..=12);
+        assert_eq!(ranges(&s), [1..=2, 5..=7, 10..=12]);
+
+        s.insert(3..=4);
+        assert_eq!(ranges(&s), [1..=7, 10..=12]);
+
+        s.insert(6..=11);
+        assert_eq!(ranges(&s), [1..=12]);
+
+        #[allow(clippy::reversed_empty_ranges)]
+        s.insert(20..=19);
+        assert_eq!(ranges(&s), [1..=12]);
+    }
+
+    #[test]
+    fn from_iter_merges() {
+        let s = set(&[8..=9, 1..=3, 2..=5, 6..=6, 11..=11]);
+
+        assert_eq!(ranges(&s), [1..=6, 8..=9, 11..=11]);
+    }
+
+    #[test]
+    fn union() {
+        let a = set(&[1..=3, 10..=12]);
+        let b = set(&[4..=5, 11..=20]);
+
+        assert_eq!(ranges(&a.union(&b)), [1..=5, 10..=20]);
+    }
+
+    #[test]
+    fn intersection() {
+        let a = set(&[1..=5, 10..=20]);
+        let b = set(&[3..=12, 15..=15, 19..=30]);
+
+        assert_eq!(
+            ranges(&a.intersection(&b)),
+            [3..=5, 10..=12, 15..=15, 19..=20]
+        );
+    }
+
+    #[test]
+    fn difference_and_remove() {
+        let a = set(&[1..=10, 20..=30]);
+        let b = set(&[3..=4, 8..=22]);
+
+        assert_eq!(ranges(&a.difference(&b)), [1..=2, 5..=7, 23..=30]);
+
+        let mut a = a;
+        a.remove(5..=25);
+        assert_eq!(ranges(&a), [1..=4, 26..=30]);
+    }
+
+    #[test]
+    fn complement() {
+        let s = set(&[3..=4, 8..=9]);
+
+        assert_eq!(ranges(&s.complement(0..=10)), [0..=2, 5..=7, 10..=10]);
+        assert_eq!(ranges(&s.complement(4..=8)), [5..=7]);
+        assert_eq!(ranges(&s.complement(3..=4)), []);
+        assert_eq!(ranges(&IntervalSet::new().complement(1..=2)), [1..=2]);
+    }
+
+    #[test]
+    fn complement_at_type_limits() {
+        let s: IntervalSet<u64> = IntervalSet::from(0..=5);
+        assert_eq!(
+            s.complement(0..=u64::MAX).iter().collect::<Vec<_>>(),
+            [6..=u64::MAX]
+        );
+
+        let s: IntervalSet<u64> = IntervalSet::from(5..=u64::MAX);
+        assert_eq!(
+            s.complement(0..=u64::MAX).iter().collect::<Vec<_>>(),
+            [0..=4]
+        );
+    }
+
+    #[test]
+    fn containment() {
+        let s = set(&[1..=5, 10..=20]);
+
+        assert!(s.contains(1));
+        assert!(s.contains(15));
+        assert!(!s.contains(7));
+        assert!(!s.contains(21));
+
+        assert!(s.contains_range(&(2..=4)));
+        assert!(!s.contains_range(&(4..=10)));
+
+        assert!(s.is_superset(&set(&[2..=3, 11..=20])));
+        assert!(!s.is_superset(&set(&[2..=3, 11..=21])));
+
+        assert!(s.is_disjoint(&set(&[6..=9, 21..=30])));
+        assert!(!s.is_disjoint(&set(&[6..=10])));
+    }
+
+    #[test]
+    fn len_and_gaps() {
+        let s = set(&[1..=5, 10..=20, 25..=25]);
+
+        assert_eq!(s.len(), 5 + 11 + 1);
+        assert_eq!(s.gaps().collect::<Vec<_>>(), [6..=9, 21..=24]);
+        assert!(IntervalSet::<i64>::new().is_empty());
+    }
+}
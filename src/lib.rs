@@ -13,3 +13,7 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod interval_set;
+
+#[cfg(test)]
+mod test_rng;
@@ -34,6 +34,29 @@ const DAY_14_INPUT: &str = include_str!("./day14/input.txt");
 const DAY_15_INPUT: &str = include_str!("./day15/input.txt");
 
 fn main() {
+    // `rope <knots>` prints how many cells each knot of a longer or shorter day 9 rope visits.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, knots] = &args[..] {
+        if command == "rope" {
+            let knots = match knots.parse::<usize>() {
+                Ok(knots) if knots > 0 => knots,
+                _ => {
+                    eprintln!("usage: rope <knots>, where <knots> is a number of at least 1");
+                    std::process::exit(2);
+                }
+            };
+            let (visited, time) = with_timing(
+                move |input| day9::visited_per_knot(input, knots),
+                DAY_9_INPUT,
+            );
+            println!("Day 9 with {knots} knots - {time:?}");
+            for (knot, visited) in visited.iter().enumerate() {
+                println!("\tknot {knot}: {visited}");
+            }
+            return;
+        }
+    }
+
     println!("Day 1 Part 1 - {:?}", with_timing(day1::part1, DAY_1_INPUT));
     println!("Day 1 Part 2 - {:?}", with_timing(day1::part2, DAY_1_INPUT));
 
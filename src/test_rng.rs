@@ -0,0 +1,19 @@
+//! Deterministic random numbers for tests, so they don't need a dependency.
+
+/// A small linear congruential generator. The same seed always gives the same numbers.
+#[derive(Clone, Debug)]
+pub(crate) struct TestRng {
+    state: u64,
+}
+
+impl TestRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A number in `0..n`.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        ((self.state >> 33) % n as u64) as usize
+    }
+}